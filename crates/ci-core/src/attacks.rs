@@ -0,0 +1,201 @@
+//! Precomputed attack sets. Sliding pieces use the classical approach: the
+//! ray in each direction is cut off behind the first blocker.
+
+use crate::{bitboard::Bitboard, board::Color, square::Square};
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+const KING_OFFSETS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+/// Directions in which the square index increases come first, so the
+/// nearest blocker of a ray is its lowest bit for the first four directions
+/// and its highest bit for the others.
+const DIRECTIONS: [(i8, i8); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (-1, 1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (1, -1),
+];
+
+const ROOK_DIRECTIONS: [usize; 4] = [0, 2, 4, 6];
+
+const BISHOP_DIRECTIONS: [usize; 4] = [1, 3, 5, 7];
+
+static KNIGHT_ATTACKS: [u64; 64] = step_attacks(&KNIGHT_OFFSETS);
+
+static KING_ATTACKS: [u64; 64] = step_attacks(&KING_OFFSETS);
+
+static PAWN_ATTACKS: [[u64; 64]; 2] = [
+    step_attacks(&[(-1, 1), (1, 1)]),
+    step_attacks(&[(-1, -1), (1, -1)]),
+];
+
+static RAYS: [[u64; 64]; 8] = rays();
+
+const fn step_attacks(offsets: &[(i8, i8)]) -> [u64; 64] {
+    let mut table = [0; 64];
+    let mut square = 0;
+
+    while square < 64 {
+        let file = (square & 7) as i8;
+        let rank = (square >> 3) as i8;
+        let mut i = 0;
+
+        while i < offsets.len() {
+            let (files, ranks) = offsets[i];
+            let (file, rank) = (file + files, rank + ranks);
+
+            if file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[square] |= 1 << (rank * 8 + file);
+            }
+            i += 1;
+        }
+        square += 1;
+    }
+
+    table
+}
+
+const fn rays() -> [[u64; 64]; 8] {
+    let mut table = [[0; 64]; 8];
+    let mut direction = 0;
+
+    while direction < 8 {
+        let (files, ranks) = DIRECTIONS[direction];
+        let mut square = 0;
+
+        while square < 64 {
+            let mut file = (square & 7) as i8 + files;
+            let mut rank = (square >> 3) as i8 + ranks;
+
+            while file >= 0 && file < 8 && rank >= 0 && rank < 8 {
+                table[direction][square] |= 1 << (rank * 8 + file);
+                file += files;
+                rank += ranks;
+            }
+            square += 1;
+        }
+        direction += 1;
+    }
+
+    table
+}
+
+fn ray_attacks(direction: usize, square: Square, occupied: Bitboard) -> Bitboard {
+    let ray = RAYS[direction][square.to_index()];
+    let blockers = Bitboard(ray & occupied.0);
+
+    let blocker = if direction < 4 {
+        blockers.first()
+    } else {
+        blockers.last()
+    };
+
+    match blocker {
+        Some(blocker) => Bitboard(ray ^ RAYS[direction][blocker.to_index()]),
+        None => Bitboard(ray),
+    }
+}
+
+pub fn knight_attacks(square: Square) -> Bitboard {
+    Bitboard(KNIGHT_ATTACKS[square.to_index()])
+}
+
+pub fn king_attacks(square: Square) -> Bitboard {
+    Bitboard(KING_ATTACKS[square.to_index()])
+}
+
+/// Squares attacked by a pawn of `color` standing on `square`.
+pub fn pawn_attacks(color: Color, square: Square) -> Bitboard {
+    Bitboard(PAWN_ATTACKS[color as usize][square.to_index()])
+}
+
+pub fn rook_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    ROOK_DIRECTIONS
+        .iter()
+        .fold(Bitboard::EMPTY, |attacks, direction| {
+            attacks | ray_attacks(*direction, square, occupied)
+        })
+}
+
+pub fn bishop_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    BISHOP_DIRECTIONS
+        .iter()
+        .fold(Bitboard::EMPTY, |attacks, direction| {
+            attacks | ray_attacks(*direction, square, occupied)
+        })
+}
+
+pub fn queen_attacks(square: Square, occupied: Bitboard) -> Bitboard {
+    rook_attacks(square, occupied) | bishop_attacks(square, occupied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squares(bitboard: Bitboard) -> Vec<Square> {
+        bitboard.squares().collect()
+    }
+
+    #[test]
+    fn test_step_attacks() {
+        assert_eq!(
+            squares(knight_attacks(Square::A1)),
+            vec![Square::C2, Square::B3]
+        );
+        assert_eq!(king_attacks(Square::E4).count(), 8);
+        assert_eq!(king_attacks(Square::H8).count(), 3);
+        assert_eq!(
+            squares(pawn_attacks(Color::White, Square::A2)),
+            vec![Square::B3]
+        );
+        assert_eq!(
+            squares(pawn_attacks(Color::Black, Square::E5)),
+            vec![Square::D4, Square::F4]
+        );
+    }
+
+    #[test]
+    fn test_sliding_attacks() {
+        let occupied = Bitboard::from_square(Square::D6) | Bitboard::from_square(Square::F4);
+
+        assert_eq!(rook_attacks(Square::D4, occupied).count(), 10);
+        assert!(rook_attacks(Square::D4, occupied).contains(Square::D6));
+        assert!(!rook_attacks(Square::D4, occupied).contains(Square::D7));
+        assert!(rook_attacks(Square::D4, occupied).contains(Square::F4));
+        assert!(!rook_attacks(Square::D4, occupied).contains(Square::G4));
+
+        assert_eq!(bishop_attacks(Square::A1, Bitboard::EMPTY).count(), 7);
+        assert_eq!(
+            squares(bishop_attacks(
+                Square::A1,
+                Bitboard::from_square(Square::C3)
+            )),
+            vec![Square::B2, Square::C3]
+        );
+        assert_eq!(queen_attacks(Square::D4, Bitboard::EMPTY).count(), 27);
+    }
+}
@@ -0,0 +1,151 @@
+use crate::square::Square;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not};
+
+/// A set of squares, one bit per square with a1 as the least significant bit.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bitboard(pub u64);
+
+impl Bitboard {
+    pub const EMPTY: Bitboard = Bitboard(0);
+    pub const LIGHT_SQUARES: Bitboard = Bitboard(0x55aa_55aa_55aa_55aa);
+    pub const DARK_SQUARES: Bitboard = Bitboard(!0x55aa_55aa_55aa_55aa);
+
+    pub fn from_square(square: Square) -> Bitboard {
+        Bitboard(1 << square.to_int())
+    }
+
+    pub fn contains(&self, square: Square) -> bool {
+        self.0 & (1 << square.to_int()) != 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The square with the lowest index in the set.
+    pub fn first(&self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Square(self.0.trailing_zeros() as u8))
+        }
+    }
+
+    /// The square with the highest index in the set.
+    pub fn last(&self) -> Option<Square> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(Square(63 - self.0.leading_zeros() as u8))
+        }
+    }
+
+    pub fn squares(&self) -> Squares {
+        Squares(*self)
+    }
+}
+
+/// Iterates over the squares of a [`Bitboard`] from a1 to h8.
+pub struct Squares(Bitboard);
+
+impl Iterator for Squares {
+    type Item = Square;
+
+    fn next(&mut self) -> Option<Square> {
+        let square = self.0.first()?;
+        self.0 .0 &= self.0 .0 - 1;
+
+        Some(square)
+    }
+}
+
+impl From<Square> for Bitboard {
+    fn from(square: Square) -> Bitboard {
+        Bitboard::from_square(square)
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+
+    fn bitand(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Bitboard {
+    type Output = Bitboard;
+
+    fn bitor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 | rhs.0)
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+
+    fn bitxor(self, rhs: Bitboard) -> Bitboard {
+        Bitboard(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl BitAndAssign for Bitboard {
+    fn bitand_assign(&mut self, rhs: Bitboard) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl BitOrAssign for Bitboard {
+    fn bitor_assign(&mut self, rhs: Bitboard) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitXorAssign for Bitboard {
+    fn bitxor_assign(&mut self, rhs: Bitboard) {
+        self.0 ^= rhs.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bitboard;
+    use crate::square::Square;
+
+    #[test]
+    fn test_squares() {
+        let bitboard = Bitboard::from_square(Square::A1)
+            | Bitboard::from_square(Square::E4)
+            | Bitboard::from_square(Square::H8);
+
+        assert_eq!(bitboard.count(), 3);
+        assert!(bitboard.contains(Square::E4));
+        assert!(!bitboard.contains(Square::E5));
+        assert_eq!(bitboard.first(), Some(Square::A1));
+        assert_eq!(bitboard.last(), Some(Square::H8));
+        assert_eq!(
+            bitboard.squares().collect::<Vec<_>>(),
+            vec![Square::A1, Square::E4, Square::H8]
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(Bitboard::EMPTY.is_empty());
+        assert_eq!(Bitboard::EMPTY.first(), None);
+        assert_eq!(Bitboard::EMPTY.last(), None);
+        assert_eq!(Bitboard::EMPTY.squares().count(), 0);
+    }
+}
@@ -1,7 +1,13 @@
-use crate::{piece::Piece, square::Square};
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
+use crate::{
+    attacks,
+    bitboard::Bitboard,
+    chess_move::ChessMove,
+    error::MoveError,
+    file::File,
+    game::{CastleType, Move, MoveType, Player},
+    piece::Piece,
+    square::Square,
+    zobrist,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
@@ -10,65 +16,470 @@ pub enum Color {
     Black,
 }
 
-struct Squares {
-    data: [Option<(Piece, Color)>; 64],
+impl Color {
+    pub fn opponent(&self) -> Color {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
+impl From<Player> for Color {
+    fn from(player: Player) -> Color {
+        match player {
+            Player::White => Color::White,
+            Player::Black => Color::Black,
+        }
+    }
 }
 
-impl Hash for Squares {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        for (idx, item) in self.data.iter().enumerate() {
-            state.write_u8(idx as u8);
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+}
 
-            if let Some((piece, color)) = item {
-                piece.hash(state);
-                color.hash(state);
-            }
+impl CastlingRights {
+    pub const ALL: CastlingRights = CastlingRights {
+        white_kingside: true,
+        white_queenside: true,
+        black_kingside: true,
+        black_queenside: true,
+    };
+
+    pub const NONE: CastlingRights = CastlingRights {
+        white_kingside: false,
+        white_queenside: false,
+        black_kingside: false,
+        black_queenside: false,
+    };
+
+    pub fn has(&self, color: Color, castle: CastleType) -> bool {
+        match (color, castle) {
+            (Color::White, CastleType::Kingside) => self.white_kingside,
+            (Color::White, CastleType::Queenside) => self.white_queenside,
+            (Color::Black, CastleType::Kingside) => self.black_kingside,
+            (Color::Black, CastleType::Queenside) => self.black_queenside,
+        }
+    }
+
+    pub fn add(&mut self, color: Color, castle: CastleType) {
+        self.set(color, castle, true);
+    }
+
+    pub fn remove(&mut self, color: Color, castle: CastleType) {
+        self.set(color, castle, false);
+    }
+
+    fn set(&mut self, color: Color, castle: CastleType, value: bool) {
+        match (color, castle) {
+            (Color::White, CastleType::Kingside) => self.white_kingside = value,
+            (Color::White, CastleType::Queenside) => self.white_queenside = value,
+            (Color::Black, CastleType::Kingside) => self.black_kingside = value,
+            (Color::Black, CastleType::Queenside) => self.black_queenside = value,
+        }
+    }
+
+    /// Removes the right that is lost when a piece leaves or arrives on
+    /// `square`, which only matters for the corners the rooks start on.
+    fn touch(&mut self, square: Square) {
+        match square {
+            Square::A1 => self.remove(Color::White, CastleType::Queenside),
+            Square::H1 => self.remove(Color::White, CastleType::Kingside),
+            Square::A8 => self.remove(Color::Black, CastleType::Queenside),
+            Square::H8 => self.remove(Color::Black, CastleType::Kingside),
+            _ => {}
         }
     }
 }
 
+#[derive(Debug, Clone)]
 pub enum Perspective {
     White,
     Black,
 }
 
+#[derive(Debug, Clone)]
 pub struct Board {
+    /// Mailbox of the position. The methods of `Board` keep the bitboards in
+    /// sync with it; rebuild them with [`Board::update_bitboards`] after
+    /// changing it directly.
     pub squares: [Option<(Piece, Color)>; 64],
+    /// Squares occupied per kind of piece, indexed by [`Piece`]
+    pieces: [Bitboard; 6],
+    /// Squares occupied per color, indexed by [`Color`]
+    colors: [Bitboard; 2],
+    /// Zobrist hash of the position, compatible with Polyglot opening books.
+    /// It is kept up to date by [`Board::play`]; recompute it with
+    /// [`Board::calculate_hash`] after changing the public fields directly.
     pub hash: u64,
     pub perspective: Perspective,
+    pub side_to_move: Color,
+    pub castling_rights: CastlingRights,
+    /// Square a pawn skipped over with a double step on the previous move
+    pub en_passant: Option<Square>,
+    /// Plies since the last capture or pawn move, for the fifty-move rule
+    pub halfmove_clock: u32,
+    /// Starts at 1 and is incremented after every move of black
+    pub fullmove_number: u32,
 }
 
 impl Board {
     pub fn new() -> Board {
-        Board {
+        let mut board = Board {
             squares: [None; 64],
+            pieces: [Bitboard::EMPTY; 6],
+            colors: [Bitboard::EMPTY; 2],
             hash: 0,
             perspective: Perspective::White,
-        }
+            side_to_move: Color::White,
+            castling_rights: CastlingRights::NONE,
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        };
+
+        board.hash = board.calculate_hash();
+        board
     }
 
     pub fn with_pieces<'a>(pieces: impl IntoIterator<Item = &'a (Square, Piece, Color)>) -> Board {
         let mut board = Board::new();
 
-        for piece in pieces.into_iter() {
-            board.squares[piece.0.to_index()] = Some((piece.1, piece.2));
+        for (square, piece, color) in pieces.into_iter() {
+            board.remove(*square);
+            board.put(*square, *piece, *color);
         }
 
-        board.hash = Board::calculate_hash(board.squares);
         board
     }
 
-    pub fn calculate_hash<'a>(squares: [Option<(Piece, Color)>; 64]) -> u64 {
-        let mut hasher = DefaultHasher::new();
-        squares.hash(&mut hasher);
+    /// Rebuilds the bitboards from [`Board::squares`], after it was changed
+    /// directly.
+    pub fn update_bitboards(&mut self) {
+        self.pieces = [Bitboard::EMPTY; 6];
+        self.colors = [Bitboard::EMPTY; 2];
+
+        for (index, square) in self.squares.iter().enumerate() {
+            if let Some((piece, color)) = square {
+                let square = Bitboard::from_square(Square(index as u8));
+                self.pieces[*piece as usize] |= square;
+                self.colors[*color as usize] |= square;
+            }
+        }
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<(Piece, Color)> {
+        self.squares[square.to_index()]
+    }
 
-        hasher.finish()
+    /// Squares holding a `piece` of `color`.
+    pub fn pieces(&self, piece: Piece, color: Color) -> Bitboard {
+        self.pieces[piece as usize] & self.colors[color as usize]
+    }
+
+    /// Squares holding a piece of `color`.
+    pub fn occupied_by(&self, color: Color) -> Bitboard {
+        self.colors[color as usize]
+    }
+
+    pub fn occupied(&self) -> Bitboard {
+        self.colors[0] | self.colors[1]
+    }
+
+    fn put(&mut self, square: Square, piece: Piece, color: Color) {
+        self.hash ^= zobrist::piece(piece, color, square);
+        self.squares[square.to_index()] = Some((piece, color));
+        self.pieces[piece as usize] |= Bitboard::from_square(square);
+        self.colors[color as usize] |= Bitboard::from_square(square);
+    }
+
+    fn remove(&mut self, square: Square) -> Option<(Piece, Color)> {
+        let removed = self.squares[square.to_index()].take();
+
+        if let Some((piece, color)) = removed {
+            self.hash ^= zobrist::piece(piece, color, square);
+            self.pieces[piece as usize] ^= Bitboard::from_square(square);
+            self.colors[color as usize] ^= Bitboard::from_square(square);
+        }
+
+        removed
+    }
+
+    /// Plays a move in Standard Algebraic Notation form on the board.
+    ///
+    /// The origin square is resolved from the pieces on the board: of all
+    /// legal moves of the moving kind of piece to the destination which match
+    /// the origin hints of the move, exactly one must remain.
+    pub fn apply_move(&mut self, chess_move: &Move) -> Result<(), MoveError> {
+        let resolved = self.resolve(chess_move)?;
+        self.play(resolved);
+
+        Ok(())
+    }
+
+    /// Finds the legal move described by a move in Standard Algebraic
+    /// Notation form, see [`Board::apply_move`].
+    pub fn resolve(&self, chess_move: &Move) -> Result<ChessMove, MoveError> {
+        let color = Color::from(chess_move.turn);
+        if color != self.side_to_move {
+            return Err(MoveError::WrongTurn(color));
+        }
+
+        let legal_moves = self.legal_moves();
+
+        if let MoveType::Castle(castle) = chess_move.move_type {
+            let (from, files) = match (color, castle) {
+                (Color::White, CastleType::Kingside) => (Square::E1, 2),
+                (Color::White, CastleType::Queenside) => (Square::E1, -2),
+                (Color::Black, CastleType::Kingside) => (Square::E8, 2),
+                (Color::Black, CastleType::Queenside) => (Square::E8, -2),
+            };
+
+            return legal_moves
+                .into_iter()
+                .find(|legal| {
+                    legal.from == from
+                        && Some(legal.to) == from.offset(files, 0)
+                        && self.piece_at(from) == Some((Piece::King, color))
+                })
+                .ok_or(MoveError::IllegalCastle(castle));
+        }
+
+        let (file, rank) = chess_move
+            .destination
+            .ok_or(MoveError::MissingDestination)?;
+        let destination = Square::new(rank, file);
+
+        let last_rank = match color {
+            Color::White => 7,
+            Color::Black => 0,
+        };
+        let promotion = match chess_move.move_type {
+            MoveType::Promotion(piece) => Some(piece),
+            _ => None,
+        };
+        let promotes = chess_move.piece == Piece::Pawn && destination.rank().to_int() == last_rank;
+
+        if promotes != promotion.is_some() || promotion == Some(Piece::King) {
+            return Err(MoveError::InvalidPromotion { destination });
+        }
+
+        let candidates = legal_moves
+            .into_iter()
+            .filter(|legal| {
+                legal.to == destination
+                    && legal.promotion == promotion
+                    && self.piece_at(legal.from) == Some((chess_move.piece, color))
+            })
+            .filter(|legal| match chess_move.origin {
+                Some((file, rank)) => {
+                    (file.is_none() || file == Some(legal.from.file()))
+                        && (rank.is_none() || rank == Some(legal.from.rank()))
+                }
+                None => true,
+            })
+            .collect::<Vec<ChessMove>>();
+
+        if candidates.is_empty() {
+            return Err(MoveError::NoCandidate {
+                piece: chess_move.piece,
+                destination,
+            });
+        }
+
+        // The notation of a promotion doesn't tell whether it captures
+        let en_passant = chess_move.piece == Piece::Pawn && self.en_passant == Some(destination);
+        let captures = match self.piece_at(destination) {
+            Some((_, occupant)) => occupant != color,
+            None => en_passant,
+        };
+        let fits = match chess_move.move_type {
+            MoveType::Move => !captures,
+            MoveType::Capture => captures,
+            MoveType::EnPassantCapture => en_passant,
+            MoveType::Promotion(_) | MoveType::Castle(_) => true,
+        };
+        if !fits {
+            return Err(MoveError::CaptureMismatch {
+                destination,
+                capture: chess_move.move_type != MoveType::Move,
+            });
+        }
+
+        match candidates[..] {
+            [resolved] => Ok(resolved),
+            _ => Err(MoveError::Ambiguous {
+                piece: chess_move.piece,
+                destination,
+                candidates: candidates.iter().map(|candidate| candidate.from).collect(),
+            }),
+        }
+    }
+
+    /// Pieces of `color` attacking `square`.
+    pub fn attackers(&self, square: Square, color: Color) -> Bitboard {
+        let occupied = self.occupied();
+        let queens = self.pieces(Piece::Queen, color);
+
+        (attacks::pawn_attacks(color.opponent(), square) & self.pieces(Piece::Pawn, color))
+            | (attacks::knight_attacks(square) & self.pieces(Piece::Knight, color))
+            | (attacks::king_attacks(square) & self.pieces(Piece::King, color))
+            | (attacks::bishop_attacks(square, occupied)
+                & (self.pieces(Piece::Bishop, color) | queens))
+            | (attacks::rook_attacks(square, occupied) & (self.pieces(Piece::Rook, color) | queens))
+    }
+
+    /// Whether any piece of `color` attacks `square`.
+    pub fn is_attacked(&self, square: Square, color: Color) -> bool {
+        !self.attackers(square, color).is_empty()
+    }
+
+    /// Whether the king of `color` is attacked. Positions without a king of
+    /// that color are never in check.
+    pub fn is_in_check(&self, color: Color) -> bool {
+        self.pieces(Piece::King, color)
+            .squares()
+            .any(|king| self.is_attacked(king, color.opponent()))
+    }
+
+    /// Plays a move produced by the move generator, see
+    /// [`Board::legal_moves`]. The move is not checked for legality.
+    pub fn play(&mut self, chess_move: ChessMove) {
+        self.make(chess_move);
+    }
+
+    /// Moves the piece and updates the position state, including the hash.
+    pub(crate) fn make(&mut self, chess_move: ChessMove) {
+        let ChessMove {
+            from,
+            to,
+            promotion,
+        } = chess_move;
+        let Some((piece, color)) = self.piece_at(from) else {
+            return;
+        };
+
+        // Take the castling rights and en passant square out of the hash
+        // before they change, and add them back once the move is made
+        self.hash ^= self.state_hash();
+        self.remove(from);
+
+        self.en_passant = None;
+        self.castling_rights.touch(from);
+        self.castling_rights.touch(to);
+
+        if piece == Piece::Pawn || self.piece_at(to).is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+
+        if color == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        match piece {
+            Piece::Pawn => {
+                // A pawn moving diagonally onto an empty square captures en passant
+                if from.file() != to.file() && self.piece_at(to).is_none() {
+                    self.remove(Square::new(from.rank(), to.file()));
+                }
+
+                if (to.rank().to_int() - from.rank().to_int()).abs() == 2 {
+                    self.en_passant = Square::from_coordinate(
+                        from.file().to_int(),
+                        (from.rank().to_int() + to.rank().to_int()) / 2,
+                    );
+                }
+            }
+            Piece::King => {
+                self.castling_rights.remove(color, CastleType::Kingside);
+                self.castling_rights.remove(color, CastleType::Queenside);
+
+                // Castling moves the king two files, the rook jumps over it
+                let rook = match to.file().to_int() - from.file().to_int() {
+                    2 => Some((File::H, File::F)),
+                    -2 => Some((File::A, File::D)),
+                    _ => None,
+                };
+
+                if let Some((rook_from, rook_to)) = rook {
+                    if let Some((rook, _)) = self.remove(Square::new(from.rank(), rook_from)) {
+                        self.put(Square::new(from.rank(), rook_to), rook, color);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.remove(to);
+        self.put(to, promotion.unwrap_or(piece), color);
+        self.side_to_move = color.opponent();
+
+        self.hash ^= self.state_hash();
+    }
+
+    /// Computes the Zobrist hash of the position from scratch, the way the
+    /// Polyglot opening book format does.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::board::Board;
+    ///
+    /// assert_eq!(Board::default().calculate_hash(), 0x463b96181691fc9c);
+    /// ```
+    pub fn calculate_hash(&self) -> u64 {
+        let pieces = self
+            .squares
+            .iter()
+            .enumerate()
+            .filter_map(|(index, square)| {
+                square.map(|(piece, color)| zobrist::piece(piece, color, Square(index as u8)))
+            })
+            .fold(0, |hash, key| hash ^ key);
+
+        pieces ^ self.state_hash()
+    }
+
+    /// Hash of everything besides the pieces: the side to move, castling
+    /// rights and en passant square.
+    fn state_hash(&self) -> u64 {
+        let mut hash = 0;
+
+        if self.side_to_move == Color::White {
+            hash ^= zobrist::white_turn();
+        }
+
+        for color in [Color::White, Color::Black] {
+            for castle in [CastleType::Kingside, CastleType::Queenside] {
+                if self.castling_rights.has(color, castle) {
+                    hash ^= zobrist::castling(color, castle);
+                }
+            }
+        }
+
+        // Like Polyglot, only count the en passant square when a pawn stands
+        // ready to capture on it
+        if let Some(square) = self.en_passant {
+            let capturers = attacks::pawn_attacks(self.side_to_move.opponent(), square)
+                & self.pieces(Piece::Pawn, self.side_to_move);
+
+            if !capturers.is_empty() {
+                hash ^= zobrist::en_passant(square.file());
+            }
+        }
+
+        hash
     }
 }
 
 impl Default for Board {
     fn default() -> Board {
-        Board::with_pieces(&[
+        let mut board = Board::with_pieces(&[
             (Square::A1, Piece::Rook, Color::White),
             (Square::B1, Piece::Knight, Color::White),
             (Square::C1, Piece::Bishop, Color::White),
@@ -101,13 +512,29 @@ impl Default for Board {
             (Square::F8, Piece::Bishop, Color::Black),
             (Square::G8, Piece::Knight, Color::Black),
             (Square::H8, Piece::Rook, Color::Black),
-        ])
+        ]);
+
+        board.castling_rights = CastlingRights::ALL;
+        board.hash = board.calculate_hash();
+        board
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{file::File, rank::Rank};
+
+    fn play(piece: Piece, turn: Player, destination: Square, move_type: MoveType) -> Move {
+        Move {
+            piece,
+            turn,
+            origin: None,
+            destination: Some((destination.file(), destination.rank())),
+            move_type,
+            result: None,
+        }
+    }
 
     #[test]
     fn test_new() {
@@ -123,4 +550,510 @@ mod tests {
 
         assert_eq!(board.squares[0], Some((Piece::Pawn, Color::White)));
     }
+
+    #[test]
+    fn test_bitboards() {
+        assert!(Board::new().occupied().is_empty());
+
+        let mut board = Board::with_pieces(&[(Square::A1, Piece::Pawn, Color::White)]);
+        assert_eq!(
+            board.pieces(Piece::Pawn, Color::White),
+            Bitboard::from_square(Square::A1)
+        );
+
+        board.squares[0] = None;
+        board.squares[63] = Some((Piece::Rook, Color::Black));
+        board.update_bitboards();
+
+        assert_eq!(board.occupied(), Bitboard::from_square(Square::H8));
+        assert_eq!(
+            board.pieces(Piece::Rook, Color::Black),
+            Bitboard::from_square(Square::H8)
+        );
+        assert!(board.occupied_by(Color::White).is_empty());
+    }
+
+    #[test]
+    fn test_apply_move() {
+        let mut board = Board::default();
+
+        board
+            .apply_move(&play(
+                Piece::Pawn,
+                Player::White,
+                Square::E4,
+                MoveType::Move,
+            ))
+            .unwrap();
+        board
+            .apply_move(&play(
+                Piece::Knight,
+                Player::Black,
+                Square::F6,
+                MoveType::Move,
+            ))
+            .unwrap();
+
+        assert_eq!(board.piece_at(Square::E2), None);
+        assert_eq!(
+            board.piece_at(Square::E4),
+            Some((Piece::Pawn, Color::White))
+        );
+        assert_eq!(board.piece_at(Square::G8), None);
+        assert_eq!(
+            board.piece_at(Square::F6),
+            Some((Piece::Knight, Color::Black))
+        );
+        assert_ne!(board.hash, Board::default().hash);
+    }
+
+    #[test]
+    fn test_apply_move_ambiguous() {
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::A1, Piece::Rook, Color::White),
+            (Square::H1, Piece::Rook, Color::White),
+        ]);
+        let mut rook_move = play(Piece::Rook, Player::White, Square::D1, MoveType::Move);
+
+        // Both rooks are stopped by the king on e1, only the a1 rook reaches d1
+        assert!(board.apply_move(&rook_move).is_ok());
+
+        let mut board = Board::with_pieces(&[
+            (Square::E2, Piece::King, Color::White),
+            (Square::A1, Piece::Rook, Color::White),
+            (Square::H1, Piece::Rook, Color::White),
+        ]);
+        rook_move.destination = Some((File::D, Rank::One));
+
+        assert_eq!(
+            board.apply_move(&rook_move),
+            Err(MoveError::Ambiguous {
+                piece: Piece::Rook,
+                destination: Square::D1,
+                candidates: vec![Square::A1, Square::H1],
+            })
+        );
+
+        rook_move.origin = Some((Some(File::H), None));
+        board.apply_move(&rook_move).unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::D1),
+            Some((Piece::Rook, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::A1),
+            Some((Piece::Rook, Color::White))
+        );
+    }
+
+    #[test]
+    fn test_apply_move_pinned() {
+        // The knight on d2 is pinned by the bishop, so Nf3 is unambiguous
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::D2, Piece::Knight, Color::White),
+            (Square::G1, Piece::Knight, Color::White),
+            (Square::B4, Piece::Bishop, Color::Black),
+        ]);
+
+        board
+            .apply_move(&play(
+                Piece::Knight,
+                Player::White,
+                Square::F3,
+                MoveType::Move,
+            ))
+            .unwrap();
+
+        assert_eq!(board.piece_at(Square::G1), None);
+        assert_eq!(
+            board.piece_at(Square::D2),
+            Some((Piece::Knight, Color::White))
+        );
+    }
+
+    #[test]
+    fn test_apply_move_illegal() {
+        let mut board = Board::default();
+
+        assert_eq!(
+            board.apply_move(&play(
+                Piece::Pawn,
+                Player::White,
+                Square::E5,
+                MoveType::Move
+            )),
+            Err(MoveError::NoCandidate {
+                piece: Piece::Pawn,
+                destination: Square::E5,
+            })
+        );
+        assert_eq!(
+            board.apply_move(&play(
+                Piece::Bishop,
+                Player::White,
+                Square::D3,
+                MoveType::Move
+            )),
+            Err(MoveError::NoCandidate {
+                piece: Piece::Bishop,
+                destination: Square::D3,
+            })
+        );
+        assert_eq!(
+            board.apply_move(&Move {
+                piece: Piece::King,
+                turn: Player::White,
+                origin: None,
+                destination: None,
+                move_type: MoveType::Castle(CastleType::Kingside),
+                result: None,
+            }),
+            Err(MoveError::IllegalCastle(CastleType::Kingside))
+        );
+    }
+
+    #[test]
+    fn test_apply_move_capture() {
+        let mut board = Board::default();
+
+        // Nothing to capture on f3
+        assert_eq!(
+            board.apply_move(&play(
+                Piece::Knight,
+                Player::White,
+                Square::F3,
+                MoveType::Capture
+            )),
+            Err(MoveError::CaptureMismatch {
+                destination: Square::F3,
+                capture: true,
+            })
+        );
+
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::E8, Piece::King, Color::Black),
+            (Square::E4, Piece::Pawn, Color::White),
+            (Square::D5, Piece::Pawn, Color::Black),
+        ]);
+
+        // A capture not written as one
+        let mut capture = play(Piece::Pawn, Player::White, Square::D5, MoveType::Move);
+        capture.origin = Some((Some(File::E), None));
+        assert_eq!(
+            board.apply_move(&capture),
+            Err(MoveError::CaptureMismatch {
+                destination: Square::D5,
+                capture: false,
+            })
+        );
+        assert_eq!(
+            board.apply_move(&Move {
+                move_type: MoveType::EnPassantCapture,
+                ..capture.clone()
+            }),
+            Err(MoveError::CaptureMismatch {
+                destination: Square::D5,
+                capture: true,
+            })
+        );
+
+        board
+            .apply_move(&Move {
+                move_type: MoveType::Capture,
+                ..capture
+            })
+            .unwrap();
+        assert_eq!(
+            board.piece_at(Square::D5),
+            Some((Piece::Pawn, Color::White))
+        );
+    }
+
+    #[test]
+    fn test_castle() {
+        let mut board = Board::with_pieces(&[
+            (Square::E8, Piece::King, Color::Black),
+            (Square::A8, Piece::Rook, Color::Black),
+            (Square::H8, Piece::Rook, Color::Black),
+            (Square::E1, Piece::King, Color::White),
+            (Square::F1, Piece::Rook, Color::White),
+        ]);
+        board.side_to_move = Color::Black;
+        board.castling_rights = CastlingRights::ALL;
+
+        let castle = |castle| Move {
+            piece: Piece::King,
+            turn: Player::Black,
+            origin: None,
+            destination: None,
+            move_type: MoveType::Castle(castle),
+            result: None,
+        };
+
+        // The rook on f1 controls f8, so black can't castle kingside
+        assert_eq!(
+            board.apply_move(&castle(CastleType::Kingside)),
+            Err(MoveError::IllegalCastle(CastleType::Kingside))
+        );
+
+        board.apply_move(&castle(CastleType::Queenside)).unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::C8),
+            Some((Piece::King, Color::Black))
+        );
+        assert_eq!(
+            board.piece_at(Square::D8),
+            Some((Piece::Rook, Color::Black))
+        );
+        assert_eq!(board.piece_at(Square::A8), None);
+        assert_eq!(board.piece_at(Square::E8), None);
+        assert!(!board.castling_rights.black_kingside);
+        assert!(!board.castling_rights.black_queenside);
+    }
+
+    #[test]
+    fn test_castling_rights() {
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::H1, Piece::Rook, Color::White),
+        ]);
+        let castle = Move {
+            piece: Piece::King,
+            turn: Player::White,
+            origin: None,
+            destination: None,
+            move_type: MoveType::Castle(CastleType::Kingside),
+            result: None,
+        };
+
+        // King and rook are in place, but the right was lost earlier
+        assert_eq!(
+            board.clone().apply_move(&castle),
+            Err(MoveError::IllegalCastle(CastleType::Kingside))
+        );
+
+        board.castling_rights.white_kingside = true;
+        board.apply_move(&castle).unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::G1),
+            Some((Piece::King, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::F1),
+            Some((Piece::Rook, Color::White))
+        );
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let mut board = Board::with_pieces(&[
+            (Square::E5, Piece::Pawn, Color::White),
+            (Square::D7, Piece::Pawn, Color::Black),
+            (Square::C7, Piece::Pawn, Color::Black),
+        ]);
+        board.side_to_move = Color::Black;
+        board
+            .apply_move(&play(
+                Piece::Pawn,
+                Player::Black,
+                Square::D5,
+                MoveType::Move,
+            ))
+            .unwrap();
+
+        assert_eq!(board.en_passant, Some(Square::D6));
+
+        let mut capture = play(
+            Piece::Pawn,
+            Player::White,
+            Square::D6,
+            MoveType::EnPassantCapture,
+        );
+        capture.origin = Some((Some(File::E), None));
+
+        board.apply_move(&capture).unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::D6),
+            Some((Piece::Pawn, Color::White))
+        );
+        assert_eq!(board.piece_at(Square::D5), None);
+        assert_eq!(board.piece_at(Square::E5), None);
+    }
+
+    #[test]
+    fn test_en_passant_expires() {
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::E8, Piece::King, Color::Black),
+            (Square::E5, Piece::Pawn, Color::White),
+            (Square::D7, Piece::Pawn, Color::Black),
+        ]);
+        board.side_to_move = Color::Black;
+
+        for (piece, turn, destination) in [
+            (Piece::Pawn, Player::Black, Square::D5),
+            (Piece::King, Player::White, Square::E2),
+            (Piece::King, Player::Black, Square::E7),
+        ] {
+            board
+                .apply_move(&play(piece, turn, destination, MoveType::Move))
+                .unwrap();
+        }
+
+        let mut capture = play(
+            Piece::Pawn,
+            Player::White,
+            Square::D6,
+            MoveType::EnPassantCapture,
+        );
+        capture.origin = Some((Some(File::E), None));
+
+        assert_eq!(
+            board.apply_move(&capture),
+            Err(MoveError::NoCandidate {
+                piece: Piece::Pawn,
+                destination: Square::D6,
+            })
+        );
+    }
+
+    #[test]
+    fn test_wrong_turn() {
+        let mut board = Board::default();
+
+        assert_eq!(
+            board.apply_move(&play(
+                Piece::Pawn,
+                Player::Black,
+                Square::E5,
+                MoveType::Move
+            )),
+            Err(MoveError::WrongTurn(Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_clocks() {
+        let mut board = Board::default();
+
+        for (piece, turn, destination) in [
+            (Piece::Knight, Player::White, Square::F3),
+            (Piece::Knight, Player::Black, Square::F6),
+            (Piece::Knight, Player::White, Square::G1),
+        ] {
+            board
+                .apply_move(&play(piece, turn, destination, MoveType::Move))
+                .unwrap();
+        }
+
+        assert_eq!(board.halfmove_clock, 3);
+        assert_eq!(board.fullmove_number, 2);
+        assert_eq!(board.side_to_move, Color::Black);
+
+        board
+            .apply_move(&play(
+                Piece::Pawn,
+                Player::Black,
+                Square::E5,
+                MoveType::Move,
+            ))
+            .unwrap();
+
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 3);
+        assert_eq!(board.en_passant, Some(Square::E6));
+    }
+
+    #[test]
+    fn test_promotion() {
+        let board = Board::with_pieces(&[
+            (Square::E7, Piece::Pawn, Color::White),
+            (Square::D8, Piece::Rook, Color::Black),
+        ]);
+
+        let mut promote = board.clone();
+        promote
+            .apply_move(&play(
+                Piece::Pawn,
+                Player::White,
+                Square::E8,
+                MoveType::Promotion(Piece::Queen),
+            ))
+            .unwrap();
+        assert_eq!(
+            promote.piece_at(Square::E8),
+            Some((Piece::Queen, Color::White))
+        );
+
+        let mut capture = board.clone();
+        let mut underpromotion = play(
+            Piece::Pawn,
+            Player::White,
+            Square::D8,
+            MoveType::Promotion(Piece::Knight),
+        );
+        underpromotion.origin = Some((Some(File::E), None));
+        capture.apply_move(&underpromotion).unwrap();
+        assert_eq!(
+            capture.piece_at(Square::D8),
+            Some((Piece::Knight, Color::White))
+        );
+        assert_eq!(capture.piece_at(Square::E8), None);
+
+        let mut missing = board.clone();
+        assert_eq!(
+            missing.apply_move(&play(
+                Piece::Pawn,
+                Player::White,
+                Square::E8,
+                MoveType::Move
+            )),
+            Err(MoveError::InvalidPromotion {
+                destination: Square::E8
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash_incremental() {
+        let board =
+            Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+                .unwrap();
+
+        for first in board.legal_moves() {
+            let mut board = board.clone();
+            board.play(first);
+            assert_eq!(board.hash, board.calculate_hash(), "{:?}", first);
+
+            for second in board.legal_moves() {
+                let mut board = board.clone();
+                board.play(second);
+                assert_eq!(board.hash, board.calculate_hash(), "{:?}", second);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hash_transposition() {
+        let mut first = Board::default();
+        let mut second = Board::default();
+
+        first.play(ChessMove::new(Square::G1, Square::F3));
+        first.play(ChessMove::new(Square::G8, Square::F6));
+        first.play(ChessMove::new(Square::B1, Square::C3));
+
+        second.play(ChessMove::new(Square::B1, Square::C3));
+        second.play(ChessMove::new(Square::G8, Square::F6));
+        second.play(ChessMove::new(Square::G1, Square::F3));
+
+        assert_eq!(first.hash, second.hash);
+        assert_ne!(first.hash, Board::default().hash);
+    }
 }
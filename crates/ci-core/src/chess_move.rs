@@ -0,0 +1,172 @@
+use std::fmt;
+
+use crate::{board::Board, error::UciError, piece::Piece, square::Square};
+
+/// A move given by its origin and destination square, as produced by the move
+/// generator. Castling is a king move of two files, en passant a pawn capture
+/// onto the en passant square.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChessMove {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Piece>,
+}
+
+impl ChessMove {
+    pub fn new(from: Square, to: Square) -> ChessMove {
+        ChessMove {
+            from,
+            to,
+            promotion: None,
+        }
+    }
+
+    /// Parses the long algebraic notation of the Universal Chess Interface,
+    /// as in `e2e4` or `e7e8q`. Whether the move is legal is not checked,
+    /// see [`Board::uci_move`] for that.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::{chess_move::ChessMove, piece::Piece, square::Square};
+    ///
+    /// let chess_move = ChessMove::from_uci("e7e8q").unwrap();
+    ///
+    /// assert_eq!(chess_move.from, Square::E7);
+    /// assert_eq!(chess_move.promotion, Some(Piece::Queen));
+    /// assert_eq!(chess_move.to_uci(), "e7e8q");
+    /// ```
+    pub fn from_uci(uci: &str) -> Result<ChessMove, UciError> {
+        let malformed = || UciError::Malformed(uci.to_string());
+
+        let from = uci
+            .get(0..2)
+            .and_then(Square::from_name)
+            .ok_or_else(malformed)?;
+        let to = uci
+            .get(2..4)
+            .and_then(Square::from_name)
+            .ok_or_else(malformed)?;
+
+        let mut rest = uci.get(4..).ok_or_else(malformed)?.chars();
+        let promotion = match (rest.next(), rest.next()) {
+            (None, _) => None,
+            (Some(piece), None) => match Piece::from_char(piece) {
+                Some(Piece::King | Piece::Pawn) | None => {
+                    return Err(UciError::InvalidPromotion(piece))
+                }
+                promotion => promotion,
+            },
+            _ => return Err(malformed()),
+        };
+
+        Ok(ChessMove {
+            from,
+            to,
+            promotion,
+        })
+    }
+
+    /// Writes the move in the long algebraic notation of the Universal Chess
+    /// Interface.
+    pub fn to_uci(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for ChessMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+
+        match self.promotion {
+            Some(piece) => write!(f, "{}", piece.to_char().to_ascii_lowercase()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Board {
+    /// Finds the legal move of this position given in UCI notation, see
+    /// [`ChessMove::from_uci`].
+    pub fn uci_move(&self, uci: &str) -> Result<ChessMove, UciError> {
+        let chess_move = ChessMove::from_uci(uci)?;
+
+        if self.legal_moves().contains(&chess_move) {
+            Ok(chess_move)
+        } else {
+            Err(UciError::IllegalMove(uci.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChessMove;
+    use crate::{board::Board, error::UciError, piece::Piece, square::Square};
+
+    #[test]
+    fn test_from_uci() {
+        assert_eq!(
+            ChessMove::from_uci("e2e4"),
+            Ok(ChessMove::new(Square::E2, Square::E4))
+        );
+        assert_eq!(
+            ChessMove::from_uci("a2a1N"),
+            Ok(ChessMove {
+                from: Square::A2,
+                to: Square::A1,
+                promotion: Some(Piece::Knight),
+            })
+        );
+
+        assert_eq!(
+            ChessMove::from_uci("e2"),
+            Err(UciError::Malformed(String::from("e2")))
+        );
+        assert_eq!(
+            ChessMove::from_uci("e2e9"),
+            Err(UciError::Malformed(String::from("e2e9")))
+        );
+        assert_eq!(
+            ChessMove::from_uci("e7e8qq"),
+            Err(UciError::Malformed(String::from("e7e8qq")))
+        );
+        assert_eq!(
+            ChessMove::from_uci("e7e8k"),
+            Err(UciError::InvalidPromotion('k'))
+        );
+        assert_eq!(
+            ChessMove::from_uci("0000"),
+            Err(UciError::Malformed(String::from("0000")))
+        );
+    }
+
+    #[test]
+    fn test_to_uci() {
+        assert_eq!(ChessMove::new(Square::G1, Square::F3).to_uci(), "g1f3");
+        assert_eq!(
+            ChessMove {
+                from: Square::B7,
+                to: Square::C8,
+                promotion: Some(Piece::Rook),
+            }
+            .to_uci(),
+            "b7c8r"
+        );
+    }
+
+    #[test]
+    fn test_uci_move() {
+        let board = Board::default();
+        let chess_move = board.uci_move("g1f3").unwrap();
+
+        assert_eq!(board.san(&chess_move), "Nf3");
+        assert_eq!(
+            board.uci_move("e2e5"),
+            Err(UciError::IllegalMove(String::from("e2e5")))
+        );
+
+        // Castling is the king moving two files
+        let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        assert_eq!(board.san(&board.uci_move("e8c8").unwrap()), "O-O-O");
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt;
+
+use crate::{
+    bitboard::Bitboard,
+    board::{Board, Color},
+    piece::Piece,
+};
+
+/// Why a game is, or can be claimed to be, drawn. Reasons are ordered from
+/// the most to the least conclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DrawReason {
+    /// The side to move has no legal move and is not in check
+    Stalemate,
+    /// Neither side has the material left to ever checkmate
+    InsufficientMaterial,
+    /// The same position occurred five times, which ends the game
+    FivefoldRepetition,
+    /// 75 moves of each side without a capture or pawn move, which ends the
+    /// game
+    SeventyFiveMoveRule,
+    /// The same position occurred three times, either player may claim a draw
+    ThreefoldRepetition,
+    /// 50 moves of each side without a capture or pawn move, either player
+    /// may claim a draw
+    FiftyMoveRule,
+}
+
+impl DrawReason {
+    /// Whether the game ends by itself, rather than on a claim of a player.
+    pub fn is_automatic(&self) -> bool {
+        !matches!(
+            self,
+            DrawReason::ThreefoldRepetition | DrawReason::FiftyMoveRule
+        )
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::FivefoldRepetition => "fivefold repetition",
+            DrawReason::SeventyFiveMoveRule => "seventy-five move rule",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FiftyMoveRule => "fifty move rule",
+        })
+    }
+}
+
+impl Board {
+    /// Whether neither side can checkmate the other by any series of legal
+    /// moves: only kings are left, plus a single knight, or any number of
+    /// bishops that all move on squares of the same color.
+    pub fn is_insufficient_material(&self) -> bool {
+        let heavy = [Piece::Pawn, Piece::Rook, Piece::Queen];
+        if [Color::White, Color::Black].iter().any(|color| {
+            heavy
+                .iter()
+                .any(|piece| !self.pieces(*piece, *color).is_empty())
+        }) {
+            return false;
+        }
+
+        let knights =
+            self.pieces(Piece::Knight, Color::White) | self.pieces(Piece::Knight, Color::Black);
+        let bishops =
+            self.pieces(Piece::Bishop, Color::White) | self.pieces(Piece::Bishop, Color::Black);
+
+        match (knights.count(), bishops.count()) {
+            (0, _) => {
+                (bishops & Bitboard::LIGHT_SQUARES).is_empty()
+                    || (bishops & Bitboard::DARK_SQUARES).is_empty()
+            }
+            (1, 0) => true,
+            _ => false,
+        }
+    }
+
+    /// Draws that follow from the position alone, see also
+    /// [`History::draw_reason`](crate::history::History::draw_reason) for
+    /// the ones that depend on the moves that led to it.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        if self.is_stalemate() {
+            Some(DrawReason::Stalemate)
+        } else if self.is_insufficient_material() {
+            Some(DrawReason::InsufficientMaterial)
+        } else if self.halfmove_clock >= 150 && !self.is_checkmate() {
+            Some(DrawReason::SeventyFiveMoveRule)
+        } else if self.halfmove_clock >= 100 && !self.is_checkmate() {
+            Some(DrawReason::FiftyMoveRule)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrawReason;
+    use crate::board::Board;
+
+    #[test]
+    fn test_insufficient_material() {
+        [
+            ("8/8/4k3/8/8/3K4/8/8 w - - 0 1", true),
+            ("8/8/4k3/8/8/3KN3/8/8 w - - 0 1", true),
+            ("8/8/4k3/8/8/3KB3/8/8 w - - 0 1", true),
+            ("8/8/4kb2/8/8/3KB3/8/8 w - - 0 1", true),
+            ("8/8/4k1b1/8/8/3KB3/8/8 w - - 0 1", false),
+            ("8/8/4kn2/8/8/3KN3/8/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3KNN2/8/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3KP3/8/8 w - - 0 1", false),
+            ("8/8/4k3/8/8/3KR3/8/8 w - - 0 1", false),
+        ]
+        .into_iter()
+        .for_each(|(fen, expected)| {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.is_insufficient_material(), expected, "{}", fen);
+        });
+    }
+
+    #[test]
+    fn test_draw_reason() {
+        [
+            (
+                "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+                Some(DrawReason::Stalemate),
+            ),
+            (
+                "8/8/4k3/8/8/3K4/8/8 w - - 0 1",
+                Some(DrawReason::InsufficientMaterial),
+            ),
+            ("8/8/4k3/8/8/3KR3/8/8 w - - 99 80", None),
+            (
+                "8/8/4k3/8/8/3KR3/8/8 w - - 100 80",
+                Some(DrawReason::FiftyMoveRule),
+            ),
+            (
+                "8/8/4k3/8/8/3KR3/8/8 w - - 150 80",
+                Some(DrawReason::SeventyFiveMoveRule),
+            ),
+            // Checkmate takes precedence over the move rules
+            ("4R1k1/5ppp/8/8/8/8/8/6K1 b - - 150 80", None),
+        ]
+        .into_iter()
+        .for_each(|(fen, expected)| {
+            let board = Board::from_fen(fen).unwrap();
+            assert_eq!(board.draw_reason(), expected, "{}", fen);
+        });
+    }
+
+    #[test]
+    fn test_is_automatic() {
+        assert!(DrawReason::SeventyFiveMoveRule.is_automatic());
+        assert!(!DrawReason::ThreefoldRepetition.is_automatic());
+    }
+}
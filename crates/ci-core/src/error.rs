@@ -0,0 +1,196 @@
+use std::{error::Error, fmt};
+
+use crate::{board::Color, game::CastleType, piece::Piece, square::Square};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveError {
+    /// The move is made by the side that is not to move
+    WrongTurn(Color),
+    /// The move does not say where the piece goes
+    MissingDestination,
+    /// None of the pieces on the board can legally make the move
+    NoCandidate {
+        piece: Piece,
+        destination: Square,
+    },
+    /// More than one piece can make the move and the origin hint does not
+    /// tell them apart
+    Ambiguous {
+        piece: Piece,
+        destination: Square,
+        candidates: Vec<Square>,
+    },
+    /// A pawn reached its last rank without choosing a piece to promote to,
+    /// or tried to promote anywhere else
+    InvalidPromotion {
+        destination: Square,
+    },
+    IllegalCastle(CastleType),
+    /// The move is written as a capture while there is nothing to capture on
+    /// its destination, or the other way around. `capture` tells whether it
+    /// was written as one.
+    CaptureMismatch {
+        destination: Square,
+        capture: bool,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::WrongTurn(color) => write!(f, "it is not {:?}'s turn to move", color),
+            MoveError::MissingDestination => f.write_str("move has no destination square"),
+            MoveError::NoCandidate { piece, destination } => {
+                write!(f, "no {:?} can legally move to {}", piece, destination)
+            }
+            MoveError::Ambiguous {
+                piece,
+                destination,
+                candidates,
+            } => {
+                let candidates = candidates
+                    .iter()
+                    .map(|square| square.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "{:?} move to {} is ambiguous between {}",
+                    piece, destination, candidates
+                )
+            }
+            MoveError::InvalidPromotion { destination } => {
+                write!(f, "invalid pawn promotion on {}", destination)
+            }
+            MoveError::IllegalCastle(castle) => write!(f, "{:?} castling is illegal", castle),
+            MoveError::CaptureMismatch {
+                destination,
+                capture: true,
+            } => write!(
+                f,
+                "move is written as a capture, but there is nothing to capture on {}",
+                destination
+            ),
+            MoveError::CaptureMismatch {
+                destination,
+                capture: false,
+            } => write!(
+                f,
+                "move captures on {}, but is not written as a capture",
+                destination
+            ),
+        }
+    }
+}
+
+impl Error for MoveError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FenError {
+    /// A FEN record has six space separated fields, the two clocks may be
+    /// omitted
+    FieldCount(usize),
+    RankCount(usize),
+    /// A rank, counted from 8 down to 1, does not describe exactly 8 squares
+    RankLength {
+        rank: usize,
+        length: usize,
+    },
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    /// A castling right is given while king or rook are not on their
+    /// initial squares
+    ImpossibleCastling(char),
+    InvalidEnPassant(String),
+    InvalidClock(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "expected 4 or 6 fields, found {}", count),
+            FenError::RankCount(count) => write!(f, "expected 8 ranks, found {}", count),
+            FenError::RankLength { rank, length } => {
+                write!(f, "rank {} describes {} squares instead of 8", rank, length)
+            }
+            FenError::InvalidPiece(piece) => write!(f, "unknown piece '{}'", piece),
+            FenError::InvalidSideToMove(side) => {
+                write!(f, "side to move must be 'w' or 'b', found '{}'", side)
+            }
+            FenError::InvalidCastling(castling) => {
+                write!(f, "invalid castling availability '{}'", castling)
+            }
+            FenError::ImpossibleCastling(flag) => write!(
+                f,
+                "castling flag '{}' given, but king or rook are not on their initial squares",
+                flag
+            ),
+            FenError::InvalidEnPassant(square) => {
+                write!(f, "invalid en passant target square '{}'", square)
+            }
+            FenError::InvalidClock(clock) => write!(f, "invalid move clock '{}'", clock),
+        }
+    }
+}
+
+impl Error for FenError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+    /// The `FEN` tag of the game does not hold a valid position
+    InvalidFen(FenError),
+    /// A move of the game cannot be played. Plies are counted from 1, the
+    /// failing move is `Game::moves[ply - 1]`
+    IllegalMove { ply: usize, error: MoveError },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::InvalidFen(error) => write!(f, "invalid start position: {}", error),
+            ReplayError::IllegalMove { ply, error } => {
+                write!(f, "illegal move at ply {}: {}", ply, error)
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::InvalidFen(error) => Some(error),
+            ReplayError::IllegalMove { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<FenError> for ReplayError {
+    fn from(error: FenError) -> Self {
+        ReplayError::InvalidFen(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum UciError {
+    /// Not two square names, optionally followed by a promotion piece
+    Malformed(String),
+    /// Promotion to a piece other than queen, rook, bishop or knight
+    InvalidPromotion(char),
+    /// The move cannot be played in the position
+    IllegalMove(String),
+}
+
+impl fmt::Display for UciError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UciError::Malformed(uci) => write!(f, "'{}' is not a UCI move", uci),
+            UciError::InvalidPromotion(piece) => {
+                write!(f, "cannot promote to '{}'", piece)
+            }
+            UciError::IllegalMove(uci) => write!(f, "'{}' is not a legal move", uci),
+        }
+    }
+}
+
+impl Error for UciError {}
@@ -0,0 +1,348 @@
+use crate::{
+    board::{Board, CastlingRights, Color},
+    error::FenError,
+    file::File,
+    game::CastleType,
+    piece::Piece,
+    rank::Rank,
+    square::Square,
+};
+
+/// The initial position of a game of chess.
+pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+impl Board {
+    /// Builds a board from a position in Forsyth-Edwards Notation.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::{board::Board, fen::STARTING_POSITION};
+    ///
+    /// let board = Board::from_fen(STARTING_POSITION).unwrap();
+    ///
+    /// assert_eq!(board.to_fen(), STARTING_POSITION);
+    /// ```
+    pub fn from_fen(fen: &str) -> Result<Board, FenError> {
+        let fields = fen.split_whitespace().collect::<Vec<&str>>();
+        let (placement, side, castling, en_passant, clocks) = match fields[..] {
+            [placement, side, castling, en_passant] => {
+                (placement, side, castling, en_passant, None)
+            }
+            [placement, side, castling, en_passant, halfmove, fullmove] => (
+                placement,
+                side,
+                castling,
+                en_passant,
+                Some((halfmove, fullmove)),
+            ),
+            _ => return Err(FenError::FieldCount(fields.len())),
+        };
+
+        let mut pieces = Vec::new();
+        let ranks = placement.split('/').collect::<Vec<&str>>();
+        if ranks.len() != 8 {
+            return Err(FenError::RankCount(ranks.len()));
+        }
+
+        // Ranks are listed from the eighth down to the first
+        for (row, rank) in ranks.iter().enumerate() {
+            let mut file = 0;
+
+            for c in rank.chars() {
+                if let Some(empty) = c.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as usize;
+                    continue;
+                }
+
+                let piece = Piece::from_char(c).ok_or(FenError::InvalidPiece(c))?;
+                let color = if c.is_ascii_uppercase() {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+
+                if file < 8 {
+                    let square = Square::new(Rank::from_index(7 - row), File::from_index(file));
+                    pieces.push((square, piece, color));
+                }
+                file += 1;
+            }
+
+            if file != 8 {
+                return Err(FenError::RankLength {
+                    rank: 8 - row,
+                    length: file,
+                });
+            }
+        }
+
+        let mut board = Board::with_pieces(&pieces);
+
+        board.side_to_move = match side {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::InvalidSideToMove(side.to_string())),
+        };
+
+        board.castling_rights = CastlingRights::NONE;
+        if castling != "-" {
+            for flag in castling.chars() {
+                let (color, castle, king, rook) = match flag {
+                    'K' => (Color::White, CastleType::Kingside, Square::E1, Square::H1),
+                    'Q' => (Color::White, CastleType::Queenside, Square::E1, Square::A1),
+                    'k' => (Color::Black, CastleType::Kingside, Square::E8, Square::H8),
+                    'q' => (Color::Black, CastleType::Queenside, Square::E8, Square::A8),
+                    _ => return Err(FenError::InvalidCastling(castling.to_string())),
+                };
+
+                if board.castling_rights.has(color, castle) {
+                    return Err(FenError::InvalidCastling(castling.to_string()));
+                }
+
+                if board.piece_at(king) != Some((Piece::King, color))
+                    || board.piece_at(rook) != Some((Piece::Rook, color))
+                {
+                    return Err(FenError::ImpossibleCastling(flag));
+                }
+
+                board.castling_rights.add(color, castle);
+            }
+        }
+
+        if en_passant != "-" {
+            let invalid = || FenError::InvalidEnPassant(en_passant.to_string());
+            let square = Square::from_name(en_passant).ok_or_else(invalid)?;
+
+            // The target lies behind a pawn of the side that just moved
+            let (rank, pawn) = match board.side_to_move {
+                Color::White => (Rank::Six, square.offset(0, -1)),
+                Color::Black => (Rank::Three, square.offset(0, 1)),
+            };
+            let pawn = pawn.and_then(|pawn| board.piece_at(pawn));
+
+            if square.rank() != rank
+                || pawn != Some((Piece::Pawn, board.side_to_move.opponent()))
+                || board.piece_at(square).is_some()
+            {
+                return Err(invalid());
+            }
+
+            board.en_passant = Some(square);
+        }
+
+        if let Some((halfmove, fullmove)) = clocks {
+            board.halfmove_clock = halfmove
+                .parse()
+                .map_err(|_| FenError::InvalidClock(halfmove.to_string()))?;
+            board.fullmove_number = fullmove
+                .parse()
+                .ok()
+                .filter(|fullmove| *fullmove > 0)
+                .ok_or_else(|| FenError::InvalidClock(fullmove.to_string()))?;
+        }
+
+        board.hash = board.calculate_hash();
+        Ok(board)
+    }
+
+    /// Serializes the position to Forsyth-Edwards Notation.
+    pub fn to_fen(&self) -> String {
+        let mut placement = Vec::with_capacity(8);
+
+        for rank in (0..8).rev() {
+            let mut row = String::new();
+            let mut empty = 0;
+
+            for file in 0..8 {
+                match self.piece_at(Square::new(Rank::from_index(rank), File::from_index(file))) {
+                    Some((piece, color)) => {
+                        if empty > 0 {
+                            row.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+
+                        row.push(match color {
+                            Color::White => piece.to_char(),
+                            Color::Black => piece.to_char().to_ascii_lowercase(),
+                        });
+                    }
+                    None => empty += 1,
+                }
+            }
+
+            if empty > 0 {
+                row.push_str(&empty.to_string());
+            }
+            placement.push(row);
+        }
+
+        let side = match self.side_to_move {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+
+        let mut castling = String::new();
+        for (color, castle, flag) in [
+            (Color::White, CastleType::Kingside, 'K'),
+            (Color::White, CastleType::Queenside, 'Q'),
+            (Color::Black, CastleType::Kingside, 'k'),
+            (Color::Black, CastleType::Queenside, 'q'),
+        ] {
+            if self.castling_rights.has(color, castle) {
+                castling.push(flag);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+
+        let en_passant = self
+            .en_passant
+            .map_or_else(|| String::from("-"), |square| square.to_string());
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement.join("/"),
+            side,
+            castling,
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::STARTING_POSITION;
+    use crate::{
+        board::{Board, Color},
+        chess_move::ChessMove,
+        error::FenError,
+        piece::Piece,
+        square::Square,
+    };
+
+    #[test]
+    fn test_starting_position() {
+        let board = Board::from_fen(STARTING_POSITION).unwrap();
+        let default = Board::default();
+
+        assert_eq!(board.squares, default.squares);
+        assert_eq!(board.castling_rights, default.castling_rights);
+        assert_eq!(board.hash, default.hash);
+        assert_eq!(default.to_fen(), STARTING_POSITION);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        ] {
+            assert_eq!(Board::from_fen(fen).unwrap().to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_position() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+                .unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::E5),
+            Some((Piece::Pawn, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::F5),
+            Some((Piece::Pawn, Color::Black))
+        );
+        assert_eq!(board.en_passant, Some(Square::F6));
+        assert_eq!(board.fullmove_number, 3);
+        assert!(board
+            .legal_moves()
+            .contains(&ChessMove::new(Square::E5, Square::F6)));
+    }
+
+    #[test]
+    fn test_without_clocks() {
+        let board = Board::from_fen("8/8/8/8/8/8/8/K6k b - -").unwrap();
+
+        assert_eq!(board.side_to_move, Color::Black);
+        assert_eq!(board.halfmove_clock, 0);
+        assert_eq!(board.fullmove_number, 1);
+    }
+
+    #[test]
+    fn test_to_fen_after_moves() {
+        let mut board = Board::default();
+        board.play(ChessMove::new(Square::E2, Square::E4));
+
+        assert_eq!(
+            board.to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        for (fen, error) in [
+            ("8/8/8/8/8/8/8/8 w -", FenError::FieldCount(3)),
+            ("8/8/8/8/8/8/8 w - -", FenError::RankCount(7)),
+            ("8/8/8/8/8/8/8/9 w - -", FenError::InvalidPiece('9')),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq -",
+                FenError::RankLength { rank: 1, length: 7 },
+            ),
+            (
+                "rnbqkbnr/ppppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+                FenError::RankLength { rank: 7, length: 9 },
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBXKBNR w KQkq -",
+                FenError::InvalidPiece('X'),
+            ),
+            (
+                "8/8/8/8/8/8/8/8 x - -",
+                FenError::InvalidSideToMove(String::from("x")),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx -",
+                FenError::InvalidCastling(String::from("KQkx")),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKq -",
+                FenError::InvalidCastling(String::from("KKq")),
+            ),
+            (
+                "rnbqkbn1/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+                FenError::ImpossibleCastling('k'),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQ1BNR w Q -",
+                FenError::ImpossibleCastling('Q'),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3",
+                FenError::InvalidEnPassant(String::from("e3")),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9",
+                FenError::InvalidEnPassant(String::from("e9")),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - x 1",
+                FenError::InvalidClock(String::from("x")),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+                FenError::InvalidClock(String::from("0")),
+            ),
+        ] {
+            assert_eq!(Board::from_fen(fen).err(), Some(error), "{}", fen);
+        }
+    }
+}
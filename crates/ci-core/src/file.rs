@@ -24,6 +24,7 @@ pub const ALL_FILES: [File; 8] = [
 ];
 
 impl File {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<File> {
         match s.to_lowercase().as_ref() {
             "a" => Some(File::A),
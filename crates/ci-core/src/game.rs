@@ -1,7 +1,14 @@
-use crate::{file::File, piece::Piece, rank::Rank};
-use std::collections::HashMap;
+use crate::{
+    board::Board,
+    error::{FenError, ReplayError},
+    file::File,
+    piece::Piece,
+    rank::Rank,
+    tags::Tags,
+};
+use std::{fmt, iter::Enumerate, slice, time::Duration};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameResult {
     WhiteWins,
     BlackWins,
@@ -12,13 +19,25 @@ pub enum GameResult {
     Unknown,
 }
 
-#[derive(Debug, PartialEq)]
+impl fmt::Display for GameResult {
+    /// Writes the result as a game termination marker, such as `1-0`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+            GameResult::Unknown => "*",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Player {
     White,
     Black,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Move {
     pub piece: Piece,
     pub turn: Player,
@@ -28,7 +47,7 @@ pub struct Move {
     pub result: Option<MoveResult>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MoveType {
     Castle(CastleType),
     Capture,
@@ -37,21 +56,453 @@ pub enum MoveType {
     Move,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MoveResult {
     Check,
     Checkmate,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CastleType {
     Kingside,
     Queenside,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Game {
-    pub moves: Vec<Move>,
-    pub tags: HashMap<String, String>,
+    /// Comments before the first move
+    pub comments: Vec<String>,
+    /// The main line of the game
+    pub moves: Vec<MoveNode>,
+    pub tags: Tags,
     pub result: GameResult,
+    /// Problems found in the game that did not keep it from being read
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// A move of a game along with its annotations and the variations that
+/// could have been played instead of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveNode {
+    pub chess_move: Move,
+    /// Numeric Annotation Glyphs, move suffixes such as `!?` are stored as
+    /// their numeric equivalent
+    pub nags: Vec<u8>,
+    /// Comments following the move
+    pub comments: Vec<String>,
+    /// Time left on the clock of the player after the move, from a
+    /// `[%clk]` command
+    pub clock: Option<Duration>,
+    /// Time the player spent on the move, from a `[%emt]` command
+    pub elapsed: Option<Duration>,
+    /// Lines that could have been played instead of this move, starting from
+    /// the position before it
+    pub variations: Vec<Variation>,
+}
+
+impl MoveNode {
+    pub fn new(chess_move: Move) -> MoveNode {
+        MoveNode {
+            chess_move,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            clock: None,
+            elapsed: None,
+            variations: Vec::new(),
+        }
+    }
+}
+
+impl From<Move> for MoveNode {
+    fn from(chess_move: Move) -> MoveNode {
+        MoveNode::new(chess_move)
+    }
+}
+
+/// A sideline of a game, see [`MoveNode::variations`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variation {
+    /// Comments before the first move
+    pub comments: Vec<String>,
+    pub moves: Vec<MoveNode>,
+}
+
+impl Variation {
+    /// Replays the variation from `board`, the position before its first
+    /// move, see [`Game::positions`].
+    pub fn positions(&self, board: Board) -> Positions<'_> {
+        Positions::new(Ok(board), &self.moves)
+    }
+
+    /// Makes variation `variation` of the move at `index` the continuation
+    /// of this line, see [`Game::promote_variation`].
+    pub fn promote_variation(&mut self, index: usize, variation: usize) -> bool {
+        promote_variation(&mut self.moves, index, variation)
+    }
+}
+
+/// A problem found in a game, like a notation that does not match the
+/// position it is played in.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// The check or checkmate marked on a move, counted in plies from 1,
+    /// differs from what the move actually delivers
+    MoveResult {
+        ply: usize,
+        notation: Option<MoveResult>,
+        actual: Option<MoveResult>,
+    },
+    /// The game cannot be replayed, so its moves were only checked up to the
+    /// failing one
+    Replay(ReplayError),
+    /// The `Result` tag differs from the result the movetext ends with
+    ResultTag { tag: String, result: GameResult },
+    /// The game ends in a position that decides it, by checkmate or a draw
+    /// that needs no claim, with a different result
+    FinalPosition {
+        result: GameResult,
+        expected: GameResult,
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |result: &Option<MoveResult>| match result {
+            Some(MoveResult::Check) => "check",
+            Some(MoveResult::Checkmate) => "checkmate",
+            None => "neither check nor checkmate",
+        };
+
+        match self {
+            Diagnostic::MoveResult {
+                ply,
+                notation,
+                actual,
+            } => write!(
+                f,
+                "move at ply {} is marked as {}, but is {}",
+                ply,
+                describe(notation),
+                describe(actual)
+            ),
+            Diagnostic::Replay(error) => write!(f, "{}", error),
+            Diagnostic::ResultTag { tag, result } => write!(
+                f,
+                "Result tag is '{}', but the game ends with {}",
+                tag, result
+            ),
+            Diagnostic::FinalPosition { result, expected } => write!(
+                f,
+                "game ends with {}, but its final position is {}",
+                result, expected
+            ),
+        }
+    }
+}
+
+impl Game {
+    /// The position the game starts from: the one in the `FEN` tag if there
+    /// is one, the standard initial position otherwise.
+    pub fn start_position(&self) -> Result<Board, FenError> {
+        match self.tags.get("FEN") {
+            Some(fen) => Board::from_fen(fen),
+            None => Ok(Board::default()),
+        }
+    }
+
+    /// Replays the game from its start position, yielding every move along
+    /// with the position after it.
+    ///
+    /// Replay stops at the first move that cannot be played, the error tells
+    /// at which ply that happened.
+    pub fn positions(&self) -> Positions<'_> {
+        Positions::new(self.start_position(), &self.moves)
+    }
+
+    /// The moves of the main line, leaving out all variations.
+    pub fn main_line(&self) -> impl Iterator<Item = &Move> {
+        self.moves.iter().map(|node| &node.chess_move)
+    }
+
+    /// Makes variation `variation` of the move at `index` of the main line
+    /// the main line, while the moves it replaces become a variation in its
+    /// place. Promoting the same variation again restores the game. Returns
+    /// whether there was such a variation.
+    ///
+    /// Comments before the first move of the variation stay with the branch
+    /// point, so they move to the demoted line.
+    pub fn promote_variation(&mut self, index: usize, variation: usize) -> bool {
+        promote_variation(&mut self.moves, index, variation)
+    }
+}
+
+fn promote_variation(moves: &mut Vec<MoveNode>, index: usize, variation: usize) -> bool {
+    let is_valid = moves
+        .get(index)
+        .and_then(|node| node.variations.get(variation))
+        .filter(|promoted| !promoted.moves.is_empty())
+        .is_some();
+    if !is_valid {
+        return false;
+    }
+
+    let mut demoted = moves.split_off(index);
+    let mut promoted = demoted[0].variations.remove(variation);
+
+    // The other alternatives to the move now branch off the promoted one
+    let mut variations = std::mem::take(&mut demoted[0].variations);
+    variations.insert(
+        variation,
+        Variation {
+            comments: promoted.comments,
+            moves: demoted,
+        },
+    );
+    promoted.moves[0].variations = variations;
+
+    moves.append(&mut promoted.moves);
+    true
+}
+
+/// Iterator over the positions of a game, see [`Game::positions`].
+pub struct Positions<'a> {
+    /// Position after the last yielded move, `None` once replay failed
+    board: Option<Board>,
+    /// Error to yield before anything else, when the start position is invalid
+    error: Option<ReplayError>,
+    moves: Enumerate<slice::Iter<'a, MoveNode>>,
+}
+
+impl<'a> Positions<'a> {
+    fn new(board: Result<Board, FenError>, moves: &'a [MoveNode]) -> Positions<'a> {
+        let (board, error) = match board {
+            Ok(board) => (Some(board), None),
+            Err(error) => (None, Some(ReplayError::InvalidFen(error))),
+        };
+
+        Positions {
+            board,
+            error,
+            moves: moves.iter().enumerate(),
+        }
+    }
+}
+
+impl<'a> Iterator for Positions<'a> {
+    type Item = Result<(&'a Move, Board), ReplayError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.error.take() {
+            return Some(Err(error));
+        }
+
+        let board = self.board.as_mut()?;
+        let (index, node) = self.moves.next()?;
+        let chess_move = &node.chess_move;
+
+        match board.apply_move(chess_move) {
+            Ok(()) => Some(Ok((chess_move, board.clone()))),
+            Err(error) => {
+                self.board = None;
+                Some(Err(ReplayError::IllegalMove {
+                    ply: index + 1,
+                    error,
+                }))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{board::Color, error::MoveError, square::Square};
+
+    fn pawn_move(turn: Player, destination: Square) -> Move {
+        Move {
+            piece: Piece::Pawn,
+            turn,
+            origin: None,
+            destination: Some((destination.file(), destination.rank())),
+            move_type: MoveType::Move,
+            result: None,
+        }
+    }
+
+    fn game(moves: Vec<Move>, fen: Option<&str>) -> Game {
+        let mut tags = Tags::new();
+        if let Some(fen) = fen {
+            tags.insert("FEN", fen);
+        }
+
+        Game {
+            comments: Vec::new(),
+            moves: moves.into_iter().map(MoveNode::new).collect(),
+            tags,
+            result: GameResult::Unknown,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        let game = game(
+            vec![
+                pawn_move(Player::White, Square::E4),
+                pawn_move(Player::Black, Square::E5),
+            ],
+            None,
+        );
+        let positions = game.positions().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].0, &game.moves[0].chess_move);
+        assert_eq!(
+            positions[0].1.piece_at(Square::E4),
+            Some((Piece::Pawn, Color::White))
+        );
+        assert_eq!(positions[1].1.side_to_move, Color::White);
+        assert_eq!(
+            positions[1].1.to_fen(),
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+        );
+    }
+
+    #[test]
+    fn test_positions_from_fen() {
+        let game = game(
+            vec![pawn_move(Player::Black, Square::E5)],
+            Some("4k3/4p3/8/8/8/8/8/4K3 b - - 0 1"),
+        );
+        let (_, board) = game.positions().next().unwrap().unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::E5),
+            Some((Piece::Pawn, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_positions_illegal_move() {
+        let game = game(
+            vec![
+                pawn_move(Player::White, Square::E4),
+                pawn_move(Player::Black, Square::E4),
+                pawn_move(Player::White, Square::D4),
+            ],
+            None,
+        );
+        let mut positions = game.positions();
+
+        assert!(positions.next().unwrap().is_ok());
+        assert!(matches!(
+            positions.next(),
+            Some(Err(ReplayError::IllegalMove {
+                ply: 2,
+                error: MoveError::NoCandidate {
+                    piece: Piece::Pawn,
+                    destination: Square::E4,
+                },
+            }))
+        ));
+        assert!(positions.next().is_none());
+    }
+
+    #[test]
+    fn test_positions_invalid_fen() {
+        let game = game(vec![], Some("8/8/8 w - - 0 1"));
+        let mut positions = game.positions();
+
+        assert!(matches!(
+            positions.next(),
+            Some(Err(ReplayError::InvalidFen(FenError::RankCount(3))))
+        ));
+        assert!(positions.next().is_none());
+    }
+
+    /// 1. e4 (1. d4 d5) (1. c4) e5
+    fn game_with_variations() -> Game {
+        let mut game = game(
+            vec![
+                pawn_move(Player::White, Square::E4),
+                pawn_move(Player::Black, Square::E5),
+            ],
+            None,
+        );
+        game.moves[0].variations = vec![
+            Variation {
+                comments: vec![String::from("Queen's pawn")],
+                moves: vec![
+                    MoveNode::new(pawn_move(Player::White, Square::D4)),
+                    MoveNode::new(pawn_move(Player::Black, Square::D5)),
+                ],
+            },
+            Variation {
+                comments: Vec::new(),
+                moves: vec![MoveNode::new(pawn_move(Player::White, Square::C4))],
+            },
+        ];
+
+        game
+    }
+
+    #[test]
+    fn test_main_line() {
+        let game = game_with_variations();
+        let main_line = game.main_line().collect::<Vec<_>>();
+
+        assert_eq!(main_line.len(), 2);
+        assert_eq!(main_line[1], &pawn_move(Player::Black, Square::E5));
+    }
+
+    #[test]
+    fn test_variation_positions() {
+        let game = game_with_variations();
+        let (_, board) = game.moves[0].variations[0]
+            .positions(game.start_position().unwrap())
+            .last()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::D5),
+            Some((Piece::Pawn, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_promote_variation() {
+        let original = game_with_variations();
+        let mut game = game_with_variations();
+
+        assert!(game.promote_variation(0, 0));
+        assert_eq!(
+            game.main_line().cloned().collect::<Vec<_>>(),
+            vec![
+                pawn_move(Player::White, Square::D4),
+                pawn_move(Player::Black, Square::D5),
+            ]
+        );
+
+        let variations = &game.moves[0].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].comments, vec![String::from("Queen's pawn")]);
+        assert_eq!(
+            variations[0]
+                .moves
+                .iter()
+                .map(|node| &node.chess_move)
+                .collect::<Vec<_>>(),
+            original.main_line().collect::<Vec<_>>()
+        );
+        assert_eq!(variations[1], original.moves[0].variations[1]);
+        assert!(game.moves[0].variations[0].moves[0].variations.is_empty());
+
+        assert!(game.promote_variation(0, 0));
+        assert_eq!(game.moves, original.moves);
+
+        assert!(!game.promote_variation(0, 2));
+        assert!(!game.promote_variation(1, 0));
+        assert_eq!(game.moves, original.moves);
+    }
 }
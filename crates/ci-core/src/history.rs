@@ -0,0 +1,155 @@
+use crate::{board::Board, chess_move::ChessMove, draw::DrawReason, error::MoveError, game::Move};
+
+/// A board along with the hashes of the positions that led to it, which is
+/// what detecting draws by repetition takes.
+///
+/// # Examples
+/// ```
+/// use ci_core::{board::Board, chess_move::ChessMove, draw::DrawReason, history::History, square::Square};
+///
+/// let mut history = History::new(Board::default());
+/// for _ in 0..2 {
+///     history.play(ChessMove::new(Square::G1, Square::F3));
+///     history.play(ChessMove::new(Square::G8, Square::F6));
+///     history.play(ChessMove::new(Square::F3, Square::G1));
+///     history.play(ChessMove::new(Square::F6, Square::G8));
+/// }
+///
+/// assert_eq!(history.repetitions(), 3);
+/// assert_eq!(history.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+/// ```
+#[derive(Debug, Clone)]
+pub struct History {
+    board: Board,
+    /// Hashes of the positions before the current one, oldest first
+    hashes: Vec<u64>,
+}
+
+impl History {
+    pub fn new(board: Board) -> History {
+        History {
+            board,
+            hashes: Vec::new(),
+        }
+    }
+
+    /// The current position.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Plays a move produced by the move generator, see [`Board::play`].
+    pub fn play(&mut self, chess_move: ChessMove) {
+        self.hashes.push(self.board.hash);
+        self.board.play(chess_move);
+    }
+
+    /// Plays a move in Standard Algebraic Notation form, see
+    /// [`Board::apply_move`].
+    pub fn apply_move(&mut self, chess_move: &Move) -> Result<(), MoveError> {
+        let resolved = self.board.resolve(chess_move)?;
+        self.play(resolved);
+
+        Ok(())
+    }
+
+    /// How often the current position occurred, counting itself.
+    pub fn repetitions(&self) -> usize {
+        // Positions from before the last capture or pawn move cannot come
+        // back, and the ones an odd number of plies ago have the other side
+        // to move
+        let reversible = self.board.halfmove_clock as usize;
+
+        1 + self
+            .hashes
+            .iter()
+            .rev()
+            .take(reversible)
+            .skip(1)
+            .step_by(2)
+            .filter(|hash| **hash == self.board.hash)
+            .count()
+    }
+
+    /// The most conclusive reason the game is, or can be claimed to be,
+    /// drawn in the current position.
+    pub fn draw_reason(&self) -> Option<DrawReason> {
+        let repetition = match self.repetitions() {
+            0..=2 => None,
+            3 | 4 => Some(DrawReason::ThreefoldRepetition),
+            _ => Some(DrawReason::FivefoldRepetition),
+        };
+
+        [self.board.draw_reason(), repetition]
+            .into_iter()
+            .flatten()
+            .min()
+    }
+}
+
+impl From<Board> for History {
+    fn from(board: Board) -> History {
+        History::new(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::History;
+    use crate::{board::Board, chess_move::ChessMove, draw::DrawReason, square::Square};
+
+    fn shuffle_knights(history: &mut History) {
+        history.play(ChessMove::new(Square::G1, Square::F3));
+        history.play(ChessMove::new(Square::G8, Square::F6));
+        history.play(ChessMove::new(Square::F3, Square::G1));
+        history.play(ChessMove::new(Square::F6, Square::G8));
+    }
+
+    #[test]
+    fn test_repetitions() {
+        let mut history = History::new(Board::default());
+        assert_eq!(history.repetitions(), 1);
+        assert_eq!(history.draw_reason(), None);
+
+        shuffle_knights(&mut history);
+        assert_eq!(history.repetitions(), 2);
+        assert_eq!(history.draw_reason(), None);
+
+        shuffle_knights(&mut history);
+        assert_eq!(history.repetitions(), 3);
+        assert_eq!(history.draw_reason(), Some(DrawReason::ThreefoldRepetition));
+
+        shuffle_knights(&mut history);
+        shuffle_knights(&mut history);
+        assert_eq!(history.repetitions(), 5);
+        assert_eq!(history.draw_reason(), Some(DrawReason::FivefoldRepetition));
+    }
+
+    #[test]
+    fn test_repetitions_reset() {
+        let mut history = History::new(Board::default());
+        shuffle_knights(&mut history);
+        shuffle_knights(&mut history);
+        history.play(ChessMove::new(Square::E2, Square::E4));
+        history.play(ChessMove::new(Square::E7, Square::E5));
+
+        // The pawn moves make the earlier positions unreachable
+        assert_eq!(history.repetitions(), 1);
+
+        shuffle_knights(&mut history);
+        assert_eq!(history.repetitions(), 2);
+    }
+
+    #[test]
+    fn test_repetitions_castling_rights() {
+        let mut history =
+            History::new(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap());
+        history.play(ChessMove::new(Square::E1, Square::E2));
+        history.play(ChessMove::new(Square::E8, Square::E7));
+        history.play(ChessMove::new(Square::E2, Square::E1));
+        history.play(ChessMove::new(Square::E7, Square::E8));
+
+        // Same placement, but castling rights were lost
+        assert_eq!(history.repetitions(), 1);
+    }
+}
@@ -1,6 +1,18 @@
+pub mod attacks;
+pub mod bitboard;
 pub mod board;
+pub mod chess_move;
+pub mod draw;
+pub mod error;
+pub mod fen;
 pub mod file;
 pub mod game;
+pub mod history;
+mod movegen;
 pub mod piece;
 pub mod rank;
+mod san;
 pub mod square;
+pub mod tags;
+pub mod time_control;
+mod zobrist;
@@ -0,0 +1,416 @@
+use crate::{
+    attacks,
+    bitboard::Bitboard,
+    board::{Board, Color},
+    chess_move::ChessMove,
+    game::{CastleType, MoveResult},
+    piece::Piece,
+    square::Square,
+};
+
+const PROMOTIONS: [Piece; 4] = [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+
+impl Board {
+    /// All legal moves for the side to move.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::board::Board;
+    ///
+    /// assert_eq!(Board::default().legal_moves().len(), 20);
+    /// ```
+    pub fn legal_moves(&self) -> Vec<ChessMove> {
+        let color = self.side_to_move;
+
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|chess_move| {
+                let mut board = self.clone();
+                board.make(*chess_move);
+                !board.is_in_check(color)
+            })
+            .collect()
+    }
+
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep, the
+    /// standard way of verifying a move generator.
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+
+        moves
+            .into_iter()
+            .map(|chess_move| {
+                let mut board = self.clone();
+                board.make(chess_move);
+                board.perft(depth - 1)
+            })
+            .sum()
+    }
+
+    /// Whether the side to move is in check.
+    pub fn is_check(&self) -> bool {
+        self.is_in_check(self.side_to_move)
+    }
+
+    /// Whether the side to move is in check and cannot get out of it.
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// Whether the side to move has no legal move without being in check.
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal_moves().is_empty()
+    }
+
+    /// The check or checkmate delivered by the move that led to this
+    /// position, as the `+` and `#` suffixes of SAN mark it.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::{board::Board, game::MoveResult};
+    ///
+    /// let board = Board::from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3").unwrap();
+    ///
+    /// assert_eq!(board.move_result(), Some(MoveResult::Checkmate));
+    /// ```
+    pub fn move_result(&self) -> Option<MoveResult> {
+        if !self.is_check() {
+            None
+        } else if self.legal_moves().is_empty() {
+            Some(MoveResult::Checkmate)
+        } else {
+            Some(MoveResult::Check)
+        }
+    }
+
+    /// Moves that follow the movement rules of the pieces, but may leave the
+    /// own king in check.
+    fn pseudo_legal_moves(&self) -> Vec<ChessMove> {
+        let color = self.side_to_move;
+        let targets = !self.occupied_by(color);
+        let occupied = self.occupied();
+        let mut moves = Vec::with_capacity(64);
+
+        let push = |moves: &mut Vec<ChessMove>, from: Square, to: Bitboard| {
+            moves.extend(to.squares().map(|to| ChessMove::new(from, to)));
+        };
+
+        for from in self.pieces(Piece::Knight, color).squares() {
+            push(&mut moves, from, attacks::knight_attacks(from) & targets);
+        }
+        for from in self.pieces(Piece::Bishop, color).squares() {
+            push(
+                &mut moves,
+                from,
+                attacks::bishop_attacks(from, occupied) & targets,
+            );
+        }
+        for from in self.pieces(Piece::Rook, color).squares() {
+            push(
+                &mut moves,
+                from,
+                attacks::rook_attacks(from, occupied) & targets,
+            );
+        }
+        for from in self.pieces(Piece::Queen, color).squares() {
+            push(
+                &mut moves,
+                from,
+                attacks::queen_attacks(from, occupied) & targets,
+            );
+        }
+        for from in self.pieces(Piece::King, color).squares() {
+            push(&mut moves, from, attacks::king_attacks(from) & targets);
+            self.castling_moves(from, color, &mut moves);
+        }
+        for from in self.pieces(Piece::Pawn, color).squares() {
+            self.pawn_moves(from, color, &mut moves);
+        }
+
+        moves
+    }
+
+    fn pawn_moves(&self, from: Square, color: Color, moves: &mut Vec<ChessMove>) {
+        let (forward, start_rank, last_rank) = match color {
+            Color::White => (1, 1, 7),
+            Color::Black => (-1, 6, 0),
+        };
+
+        let mut push = |to: Square| {
+            if to.rank().to_int() == last_rank {
+                moves.extend(PROMOTIONS.iter().map(|piece| ChessMove {
+                    from,
+                    to,
+                    promotion: Some(*piece),
+                }));
+            } else {
+                moves.push(ChessMove::new(from, to));
+            }
+        };
+
+        let occupied = self.occupied();
+        if let Some(to) = from.offset(0, forward).filter(|to| !occupied.contains(*to)) {
+            push(to);
+
+            if from.rank().to_int() == start_rank {
+                if let Some(to) = from
+                    .offset(0, 2 * forward)
+                    .filter(|to| !occupied.contains(*to))
+                {
+                    push(to);
+                }
+            }
+        }
+
+        let mut targets = self.occupied_by(color.opponent());
+        if let Some(en_passant) = self.en_passant {
+            targets |= Bitboard::from_square(en_passant);
+        }
+
+        for to in (attacks::pawn_attacks(color, from) & targets).squares() {
+            push(to);
+        }
+    }
+
+    fn castling_moves(&self, from: Square, color: Color, moves: &mut Vec<ChessMove>) {
+        let home = match color {
+            Color::White => Square::E1,
+            Color::Black => Square::E8,
+        };
+
+        if from != home || self.is_attacked(home, color.opponent()) {
+            return;
+        }
+
+        for castle in [CastleType::Kingside, CastleType::Queenside] {
+            if !self.castling_rights.has(color, castle) {
+                continue;
+            }
+
+            // Squares between king and rook, and the ones the king crosses
+            let (rook, between, crossed) = match castle {
+                CastleType::Kingside => (3, &[1, 2][..], &[1, 2][..]),
+                CastleType::Queenside => (-4, &[-1, -2, -3][..], &[-1, -2][..]),
+            };
+
+            let has_rook = home
+                .offset(rook, 0)
+                .and_then(|square| self.piece_at(square))
+                == Some((Piece::Rook, color));
+            let is_empty = between
+                .iter()
+                .filter_map(|files| home.offset(*files, 0))
+                .all(|square| self.piece_at(square).is_none());
+            let is_safe = crossed
+                .iter()
+                .filter_map(|files| home.offset(*files, 0))
+                .all(|square| !self.is_attacked(square, color.opponent()));
+
+            if has_rook && is_empty && is_safe {
+                if let Some(to) = home.offset(crossed[1], 0) {
+                    moves.push(ChessMove::new(from, to));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        board::{Board, Color},
+        chess_move::ChessMove,
+        game::MoveResult,
+        piece::Piece,
+        square::Square,
+    };
+
+    fn kiwipete() -> Board {
+        Board::from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap()
+    }
+
+    fn position_3() -> Board {
+        Board::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap()
+    }
+
+    #[test]
+    fn test_perft_initial_position() {
+        let board = Board::default();
+
+        assert_eq!(board.perft(1), 20);
+        assert_eq!(board.perft(2), 400);
+        assert_eq!(board.perft(3), 8_902);
+        assert_eq!(board.perft(4), 197_281);
+    }
+
+    #[test]
+    fn test_perft_kiwipete() {
+        let board = kiwipete();
+
+        assert_eq!(board.perft(1), 48);
+        assert_eq!(board.perft(2), 2_039);
+        assert_eq!(board.perft(3), 97_862);
+    }
+
+    #[test]
+    fn test_perft_position_3() {
+        let board = position_3();
+
+        assert_eq!(board.perft(1), 14);
+        assert_eq!(board.perft(2), 191);
+        assert_eq!(board.perft(3), 2_812);
+        assert_eq!(board.perft(4), 43_238);
+    }
+
+    #[test]
+    fn test_perft_position_4() {
+        let board =
+            Board::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+
+        assert_eq!(board.perft(1), 6);
+        assert_eq!(board.perft(2), 264);
+        assert_eq!(board.perft(3), 9_467);
+    }
+
+    #[test]
+    fn test_perft_position_5() {
+        let board =
+            Board::from_fen("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8").unwrap();
+
+        assert_eq!(board.perft(1), 44);
+        assert_eq!(board.perft(2), 1_486);
+        assert_eq!(board.perft(3), 62_379);
+    }
+
+    #[test]
+    fn test_pinned_piece() {
+        let board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::E2, Piece::Rook, Color::White),
+            (Square::E8, Piece::Rook, Color::Black),
+        ]);
+
+        // The rook may only move along the pin
+        assert!(board
+            .legal_moves()
+            .iter()
+            .filter(|chess_move| chess_move.from == Square::E2)
+            .all(|chess_move| chess_move.to.file() == Square::E2.file()));
+    }
+
+    #[test]
+    fn test_check_evasion() {
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::A2, Piece::Rook, Color::White),
+            (Square::E8, Piece::Rook, Color::Black),
+            (Square::H8, Piece::King, Color::Black),
+        ]);
+        board.side_to_move = Color::White;
+
+        let mut moves = board.legal_moves();
+        moves.sort_by_key(|chess_move| (chess_move.from.0, chess_move.to.0));
+
+        assert_eq!(
+            moves,
+            vec![
+                ChessMove::new(Square::E1, Square::D1),
+                ChessMove::new(Square::E1, Square::F1),
+                ChessMove::new(Square::E1, Square::D2),
+                ChessMove::new(Square::E1, Square::F2),
+                ChessMove::new(Square::A2, Square::E2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_en_passant() {
+        let mut board = Board::with_pieces(&[
+            (Square::E1, Piece::King, Color::White),
+            (Square::E8, Piece::King, Color::Black),
+            (Square::E5, Piece::Pawn, Color::White),
+            (Square::D7, Piece::Pawn, Color::Black),
+        ]);
+        board.side_to_move = Color::Black;
+        board.play(ChessMove::new(Square::D7, Square::D5));
+
+        assert_eq!(board.en_passant, Some(Square::D6));
+        assert!(board
+            .legal_moves()
+            .contains(&ChessMove::new(Square::E5, Square::D6)));
+
+        board.play(ChessMove::new(Square::E5, Square::D6));
+
+        assert_eq!(board.piece_at(Square::D5), None);
+        assert_eq!(
+            board.piece_at(Square::D6),
+            Some((Piece::Pawn, Color::White))
+        );
+        assert_eq!(board.en_passant, None);
+    }
+
+    #[test]
+    fn test_castling_rights() {
+        let mut board = kiwipete();
+        board.play(ChessMove::new(Square::H1, Square::G1));
+
+        assert!(!board.castling_rights.white_kingside);
+        assert!(board.castling_rights.white_queenside);
+        assert_eq!(board.side_to_move, Color::Black);
+
+        board.play(ChessMove::new(Square::E8, Square::C8));
+
+        assert_eq!(
+            board.piece_at(Square::D8),
+            Some((Piece::Rook, Color::Black))
+        );
+        assert!(!board.castling_rights.black_kingside);
+        assert!(!board.castling_rights.black_queenside);
+    }
+
+    #[test]
+    fn test_check() {
+        let board =
+            Board::from_fen("rnbqkbnr/ppp2ppp/3p4/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3")
+                .unwrap();
+        assert!(!board.is_check());
+        assert_eq!(board.move_result(), None);
+
+        let board =
+            Board::from_fen("rnbqkbnr/ppp2ppp/3p4/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 1 3")
+                .unwrap();
+        assert!(board.is_check());
+        assert!(!board.is_checkmate());
+        assert_eq!(board.move_result(), Some(MoveResult::Check));
+    }
+
+    #[test]
+    fn test_checkmate() {
+        // Scholar's mate
+        let board =
+            Board::from_fen("r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4")
+                .unwrap();
+
+        assert!(board.is_checkmate());
+        assert!(!board.is_stalemate());
+        assert_eq!(board.move_result(), Some(MoveResult::Checkmate));
+    }
+
+    #[test]
+    fn test_stalemate() {
+        let board = Board::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+
+        assert!(board.is_stalemate());
+        assert!(!board.is_checkmate());
+        assert_eq!(board.move_result(), None);
+    }
+}
@@ -7,3 +7,30 @@ pub enum Piece {
     Knight,
     Pawn,
 }
+
+impl Piece {
+    /// Parses the English piece letter used by SAN and FEN, in either case.
+    pub fn from_char(c: char) -> Option<Piece> {
+        match c.to_ascii_uppercase() {
+            'K' => Some(Piece::King),
+            'Q' => Some(Piece::Queen),
+            'R' => Some(Piece::Rook),
+            'B' => Some(Piece::Bishop),
+            'N' => Some(Piece::Knight),
+            'P' => Some(Piece::Pawn),
+            _ => None,
+        }
+    }
+
+    /// The uppercase English piece letter.
+    pub fn to_char(&self) -> char {
+        match self {
+            Piece::King => 'K',
+            Piece::Queen => 'Q',
+            Piece::Rook => 'R',
+            Piece::Bishop => 'B',
+            Piece::Knight => 'N',
+            Piece::Pawn => 'P',
+        }
+    }
+}
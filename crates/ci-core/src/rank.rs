@@ -24,6 +24,7 @@ pub const ALL_RANKS: [Rank; 8] = [
 ];
 
 impl Rank {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Rank> {
         match s {
             "1" => Some(Rank::One),
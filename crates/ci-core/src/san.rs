@@ -0,0 +1,159 @@
+use crate::{board::Board, chess_move::ChessMove, game::MoveResult, piece::Piece};
+
+impl Board {
+    /// Writes a legal move of this position in Standard Algebraic Notation,
+    /// with the least disambiguation the PGN specification allows and a
+    /// `+` or `#` suffix for moves giving check or checkmate.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_core::{board::Board, chess_move::ChessMove, square::Square};
+    ///
+    /// let board = Board::default();
+    ///
+    /// assert_eq!(board.san(&ChessMove::new(Square::G1, Square::F3)), "Nf3");
+    /// ```
+    pub fn san(&self, chess_move: &ChessMove) -> String {
+        let mut san = self.san_without_suffix(chess_move);
+
+        let mut board = self.clone();
+        board.play(*chess_move);
+        match board.move_result() {
+            Some(MoveResult::Check) => san.push('+'),
+            Some(MoveResult::Checkmate) => san.push('#'),
+            None => {}
+        }
+
+        san
+    }
+
+    fn san_without_suffix(&self, chess_move: &ChessMove) -> String {
+        let ChessMove {
+            from,
+            to,
+            promotion,
+        } = *chess_move;
+        let Some((piece, color)) = self.piece_at(from) else {
+            return String::from("--");
+        };
+
+        if piece == Piece::King {
+            match to.file().to_int() - from.file().to_int() {
+                2 => return String::from("O-O"),
+                -2 => return String::from("O-O-O"),
+                _ => {}
+            }
+        }
+
+        // Pawns capture diagonally, also onto the empty en passant square
+        let is_capture =
+            self.piece_at(to).is_some() || (piece == Piece::Pawn && from.file() != to.file());
+        let mut san = String::new();
+
+        if piece == Piece::Pawn {
+            if is_capture {
+                san.push_str(from.file().to_str());
+            }
+        } else {
+            san.push(piece.to_char());
+
+            let others = self
+                .legal_moves()
+                .into_iter()
+                .filter(|other| {
+                    other.to == to
+                        && other.from != from
+                        && self.piece_at(other.from) == Some((piece, color))
+                })
+                .map(|other| other.from)
+                .collect::<Vec<_>>();
+
+            // The file is preferred, the rank is used when it tells the
+            // pieces apart where the file does not, both when neither does
+            if !others.is_empty() {
+                let shares_file = others.iter().any(|other| other.file() == from.file());
+                let shares_rank = others.iter().any(|other| other.rank() == from.rank());
+
+                if !shares_file || shares_rank {
+                    san.push_str(from.file().to_str());
+                }
+                if shares_file {
+                    san.push_str(from.rank().to_str());
+                }
+            }
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san.push_str(&to.to_string());
+
+        if let Some(promotion) = promotion {
+            san.push('=');
+            san.push(promotion.to_char());
+        }
+
+        san
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{board::Board, chess_move::ChessMove, piece::Piece, square::Square};
+
+    fn san(fen: &str, from: Square, to: Square) -> String {
+        Board::from_fen(fen).unwrap().san(&ChessMove::new(from, to))
+    }
+
+    #[test]
+    fn test_san() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(san(start, Square::E2, Square::E4), "e4");
+        assert_eq!(san(start, Square::B1, Square::C3), "Nc3");
+
+        let kiwipete = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+        assert_eq!(san(kiwipete, Square::E1, Square::G1), "O-O");
+        assert_eq!(san(kiwipete, Square::E1, Square::C1), "O-O-O");
+        assert_eq!(san(kiwipete, Square::D5, Square::E6), "dxe6");
+        assert_eq!(san(kiwipete, Square::E5, Square::F7), "Nxf7");
+        assert_eq!(san(kiwipete, Square::F3, Square::H3), "Qxh3");
+    }
+
+    #[test]
+    fn test_san_disambiguation() {
+        // Knights on b1 and f3 can both reach d2
+        let fen = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(san(fen, Square::B1, Square::D2), "Nbd2");
+
+        // Rooks on the same file
+        let fen = "4k3/R7/8/8/8/8/R7/4K3 w - - 0 1";
+        assert_eq!(san(fen, Square::A2, Square::A5), "R2a5");
+
+        // Three queens reaching b2, one needs both file and rank
+        let fen = "4k3/8/8/8/8/Q7/8/Q1Q4K w - - 0 1";
+        assert_eq!(san(fen, Square::A1, Square::B2), "Qa1b2");
+        assert_eq!(san(fen, Square::A3, Square::B2), "Q3b2");
+        assert_eq!(san(fen, Square::C1, Square::B2), "Qcb2");
+
+        // A pinned knight does not need to be told apart
+        let fen = "4k3/8/8/8/4r3/8/4N3/2N1K3 w - - 0 1";
+        assert_eq!(san(fen, Square::C1, Square::D3), "Nd3");
+    }
+
+    #[test]
+    fn test_san_special_moves() {
+        let fen = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3";
+        assert_eq!(san(fen, Square::E5, Square::F6), "exf6");
+
+        let board = Board::from_fen("1n2k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let promotion = ChessMove {
+            from: Square::A7,
+            to: Square::B8,
+            promotion: Some(Piece::Queen),
+        };
+        assert_eq!(board.san(&promotion), "axb8=Q+");
+
+        let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+        assert_eq!(san(fen, Square::A1, Square::A8), "Ra8#");
+    }
+}
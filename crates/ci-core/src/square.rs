@@ -1,6 +1,7 @@
 use crate::{file::File, rank::Rank};
+use std::fmt;
 
-#[derive(PartialEq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
 pub struct Square(pub u8);
 
 impl Square {
@@ -9,7 +10,7 @@ impl Square {
     }
 
     pub fn rank(&self) -> Rank {
-        Rank::from_index(self.0 as usize >> 3 as u8)
+        Rank::from_index(self.0 as usize >> 3)
     }
 
     pub fn file(&self) -> File {
@@ -28,6 +29,34 @@ impl Square {
         (self.file().to_int(), self.rank().to_int())
     }
 
+    /// Inverse of [`Square::coordinate`], `None` when the coordinate lies
+    /// outside of the board.
+    pub fn from_coordinate(file: i8, rank: i8) -> Option<Square> {
+        if (0..8).contains(&file) && (0..8).contains(&rank) {
+            Some(Square((rank as u8) << 3 ^ (file as u8)))
+        } else {
+            None
+        }
+    }
+
+    /// Parses a square name such as `e4`.
+    pub fn from_name(name: &str) -> Option<Square> {
+        let mut chars = name.chars().map(|c| c.to_string());
+        let file = chars.next().and_then(|file| File::from_str(&file))?;
+        let rank = chars.next().and_then(|rank| Rank::from_str(&rank))?;
+
+        match chars.next() {
+            Some(_) => None,
+            None => Some(Square::new(rank, file)),
+        }
+    }
+
+    /// Returns the square `files` and `ranks` away from this one.
+    pub fn offset(&self, files: i8, ranks: i8) -> Option<Square> {
+        let (file, rank) = self.coordinate();
+        Square::from_coordinate(file + files, rank + ranks)
+    }
+
     pub const A1: Square = Square(0);
     pub const B1: Square = Square(1);
     pub const C1: Square = Square(2);
@@ -94,10 +123,25 @@ impl Square {
     pub const H8: Square = Square(63);
 }
 
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file().to_str(), self.rank().to_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Square::from_name("e4"), Some(Square::E4));
+        assert_eq!(Square::from_name("h8"), Some(Square::H8));
+        assert_eq!(Square::from_name("e"), None);
+        assert_eq!(Square::from_name("e9"), None);
+        assert_eq!(Square::from_name("e44"), None);
+    }
+
     #[test]
     fn test_new() {
         let square = Square::new(Rank::One, File::A);
@@ -122,4 +166,17 @@ mod tests {
         assert_eq!(square.rank(), Rank::Five);
         assert_eq!(square.file(), File::A);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(Square::E4.offset(1, 2), Some(Square::F6));
+        assert_eq!(Square::H1.offset(1, 0), None);
+        assert_eq!(Square::A8.offset(0, 1), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Square::E4.to_string(), "e4");
+        assert_eq!(Square::H8.to_string(), "h8");
+    }
+}
@@ -0,0 +1,324 @@
+use std::{fmt, ops::Index, slice};
+
+use crate::{game::GameResult, time_control::TimeControl};
+
+/// The tag pairs of a game, in the order they were added.
+///
+/// Adding a tag that is already present replaces its value in place, so each
+/// name occurs at most once.
+///
+/// # Examples
+/// ```
+/// use ci_core::tags::{Date, Tags};
+///
+/// let mut tags = Tags::new();
+/// tags.insert("White", "Fischer, Robert J.");
+/// tags.insert("Date", "1992.??.??");
+///
+/// assert_eq!(tags["White"], "Fischer, Robert J.");
+/// assert_eq!(tags.date().unwrap().year, Some(1992));
+/// assert_eq!(tags.iter().map(|(name, _)| name).collect::<Vec<_>>(), ["White", "Date"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags(Vec<(String, String)>);
+
+impl Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the tag `name`, returning its previous value.
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        let name = name.into();
+        let value = value.into();
+
+        match self.0.iter_mut().find(|(tag, _)| *tag == name) {
+            Some((_, previous)) => Some(std::mem::replace(previous, value)),
+            None => {
+                self.0.push((name, value));
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        let index = self.0.iter().position(|(tag, _)| tag == name)?;
+        Some(self.0.remove(index).1)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The tags as name and value pairs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Name of the tournament or match event.
+    pub fn event(&self) -> Option<&str> {
+        self.get("Event")
+    }
+
+    /// Location of the event.
+    pub fn site(&self) -> Option<&str> {
+        self.get("Site")
+    }
+
+    /// Starting date of the game, `None` when missing or malformed.
+    pub fn date(&self) -> Option<Date> {
+        self.get("Date").and_then(Date::parse)
+    }
+
+    /// Playing round of the game within the event.
+    pub fn round(&self) -> Option<&str> {
+        self.get("Round")
+    }
+
+    /// Player of the white pieces, in `Last, First` format.
+    pub fn white(&self) -> Option<&str> {
+        self.get("White")
+    }
+
+    /// Player of the black pieces, in `Last, First` format.
+    pub fn black(&self) -> Option<&str> {
+        self.get("Black")
+    }
+
+    /// Result of the game, `None` when missing or not one of `1-0`, `0-1`,
+    /// `1/2-1/2` and `*`.
+    pub fn result(&self) -> Option<GameResult> {
+        match self.get("Result")? {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            "*" => Some(GameResult::Unknown),
+            _ => None,
+        }
+    }
+
+    /// Time control of the game, `None` when missing or malformed.
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.get("TimeControl").and_then(TimeControl::parse)
+    }
+}
+
+impl Index<&str> for Tags {
+    type Output = String;
+
+    /// Returns the value of the tag `name`.
+    ///
+    /// # Panics
+    /// Panics when the tag is not present.
+    fn index(&self, name: &str) -> &String {
+        self.0
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("no tag named '{}'", name))
+    }
+}
+
+impl<N: Into<String>, V: Into<String>> FromIterator<(N, V)> for Tags {
+    fn from_iter<I: IntoIterator<Item = (N, V)>>(iter: I) -> Self {
+        let mut tags = Tags::new();
+        for (name, value) in iter {
+            tags.insert(name, value);
+        }
+
+        tags
+    }
+}
+
+impl<'a> IntoIterator for &'a Tags {
+    type Item = &'a (String, String);
+    type IntoIter = slice::Iter<'a, (String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+/// A date in `YYYY.MM.DD` format, where unknown parts are written as
+/// question marks, as in `1992.??.??`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: Option<u16>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+}
+
+impl Date {
+    /// Parses a date such as `1992.11.04` or `1992.??.??`.
+    pub fn parse(date: &str) -> Option<Date> {
+        let mut parts = date.split('.');
+        let year = Self::part(parts.next()?, 4, 0..=9999)?;
+        let month = Self::part(parts.next()?, 2, 1..=12)?;
+        let day = Self::part(parts.next()?, 2, 1..=31)?;
+
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Date {
+            year,
+            month: month.map(|month| month as u8),
+            day: day.map(|day| day as u8),
+        })
+    }
+
+    /// Parses a part of `length` digits within `range`, or as many question
+    /// marks when unknown.
+    fn part(
+        part: &str,
+        length: usize,
+        range: std::ops::RangeInclusive<u16>,
+    ) -> Option<Option<u16>> {
+        if part.len() != length {
+            return None;
+        }
+
+        if part.chars().all(|c| c == '?') {
+            return Some(None);
+        }
+
+        part.chars()
+            .all(|c| c.is_ascii_digit())
+            .then(|| part.parse().ok())
+            .flatten()
+            .filter(|value| range.contains(value))
+            .map(Some)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.year {
+            Some(year) => write!(f, "{:04}", year)?,
+            None => f.write_str("????")?,
+        }
+
+        for part in [self.month, self.day] {
+            match part {
+                Some(part) => write!(f, ".{:02}", part)?,
+                None => f.write_str(".??")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Date, Tags};
+    use crate::{game::GameResult, time_control::TimeControl};
+
+    #[test]
+    fn test_order() {
+        let mut tags = [("Event", "A"), ("Site", "B"), ("ECO", "C42")]
+            .into_iter()
+            .collect::<Tags>();
+
+        assert_eq!(tags.insert("Site", "C"), Some(String::from("B")));
+        assert_eq!(tags.insert("Annotator", "D"), None);
+        assert_eq!(tags.remove("Event"), Some(String::from("A")));
+
+        assert_eq!(
+            tags.iter().collect::<Vec<_>>(),
+            vec![("Site", "C"), ("ECO", "C42"), ("Annotator", "D")]
+        );
+        assert!(!tags.contains("Event"));
+        assert_eq!(tags.len(), 3);
+    }
+
+    #[test]
+    fn test_seven_tag_roster() {
+        let tags = [
+            ("Event", "F/S Return Match"),
+            ("Site", "Belgrade, Serbia JUG"),
+            ("Date", "1992.11.04"),
+            ("Round", "29"),
+            ("White", "Fischer, Robert J."),
+            ("Black", "Spassky, Boris V."),
+            ("Result", "1/2-1/2"),
+        ]
+        .into_iter()
+        .collect::<Tags>();
+
+        assert_eq!(tags.event(), Some("F/S Return Match"));
+        assert_eq!(tags.site(), Some("Belgrade, Serbia JUG"));
+        assert_eq!(
+            tags.date(),
+            Some(Date {
+                year: Some(1992),
+                month: Some(11),
+                day: Some(4),
+            })
+        );
+        assert_eq!(tags.round(), Some("29"));
+        assert_eq!(tags.white(), Some("Fischer, Robert J."));
+        assert_eq!(tags.black(), Some("Spassky, Boris V."));
+        assert_eq!(tags.result(), Some(GameResult::Draw));
+
+        assert_eq!(Tags::new().event(), None);
+        assert_eq!(Tags::new().time_control(), None);
+        assert_eq!(
+            [("TimeControl", "-")]
+                .into_iter()
+                .collect::<Tags>()
+                .time_control(),
+            Some(TimeControl::Unlimited)
+        );
+        assert_eq!(Tags::new().result(), None);
+    }
+
+    #[test]
+    fn test_date() {
+        assert_eq!(
+            Date::parse("1992.??.??"),
+            Some(Date {
+                year: Some(1992),
+                month: None,
+                day: None,
+            })
+        );
+        assert_eq!(
+            Date::parse("????.??.??").map(|date| date.to_string()),
+            Some(String::from("????.??.??"))
+        );
+        assert_eq!(
+            Date::parse("2021.11.30").map(|date| date.to_string()),
+            Some(String::from("2021.11.30"))
+        );
+
+        for date in [
+            "1992",
+            "1992.11",
+            "1992.13.01",
+            "1992.1.01",
+            "92.11.04",
+            "1992.11.04.1",
+            "19?2.11.04",
+        ] {
+            assert_eq!(Date::parse(date), None, "{}", date);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+/// The time control of a game, as given by the `TimeControl` tag.
+///
+/// # Examples
+/// ```
+/// use ci_core::time_control::{Period, TimeControl};
+///
+/// assert_eq!(
+///     TimeControl::parse("300+3"),
+///     Some(TimeControl::Periods(vec![Period::SuddenDeath {
+///         seconds: 300,
+///         increment: 3,
+///     }]))
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeControl {
+    /// `?`, the time control is not known
+    Unknown,
+    /// `-`, the game was played without a clock
+    Unlimited,
+    /// The periods the game is played in, in order. The last one repeats
+    /// until the game ends.
+    Periods(Vec<Period>),
+}
+
+/// A period of a [`TimeControl`], times being in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// `moves/seconds`, a number of moves to be made within the time, as in
+    /// `40/9000`. An increment may be added as in `40/5400+30`.
+    Moves {
+        moves: u32,
+        seconds: u32,
+        increment: u32,
+    },
+    /// `seconds` or `seconds+increment`, the rest of the game is to be played
+    /// within the time, as in `300+3`
+    SuddenDeath { seconds: u32, increment: u32 },
+    /// `*seconds`, an hourglass where the time one player uses is added to
+    /// the other's, as in `*180`
+    Sandclock { seconds: u32 },
+}
+
+impl TimeControl {
+    /// Parses the value of a `TimeControl` tag, periods being separated by
+    /// colons as in `40/7200:3600`.
+    pub fn parse(value: &str) -> Option<TimeControl> {
+        match value {
+            "?" => Some(TimeControl::Unknown),
+            "-" => Some(TimeControl::Unlimited),
+            _ => value
+                .split(':')
+                .map(Period::parse)
+                .collect::<Option<Vec<_>>>()
+                .map(TimeControl::Periods),
+        }
+    }
+}
+
+impl Period {
+    fn parse(period: &str) -> Option<Period> {
+        if let Some(seconds) = period.strip_prefix('*') {
+            return Some(Period::Sandclock {
+                seconds: number(seconds)?,
+            });
+        }
+
+        let (time, increment) = match period.split_once('+') {
+            Some((time, increment)) => (time, number(increment)?),
+            None => (period, 0),
+        };
+
+        match time.split_once('/') {
+            Some((moves, seconds)) => Some(Period::Moves {
+                moves: number(moves)?,
+                seconds: number(seconds)?,
+                increment,
+            }),
+            None => Some(Period::SuddenDeath {
+                seconds: number(time)?,
+                increment,
+            }),
+        }
+    }
+}
+
+/// Parses a number made of digits only, without the sign `str::parse`
+/// would accept.
+fn number(digits: &str) -> Option<u32> {
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    digits.parse().ok()
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeControl::Unknown => f.write_str("?"),
+            TimeControl::Unlimited => f.write_str("-"),
+            TimeControl::Periods(periods) => {
+                for (index, period) in periods.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(":")?;
+                    }
+                    write!(f, "{}", period)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (seconds, increment) = match self {
+            Period::Moves {
+                moves,
+                seconds,
+                increment,
+            } => {
+                write!(f, "{}/", moves)?;
+                (seconds, increment)
+            }
+            Period::SuddenDeath { seconds, increment } => (seconds, increment),
+            Period::Sandclock { seconds } => return write!(f, "*{}", seconds),
+        };
+
+        match increment {
+            0 => write!(f, "{}", seconds),
+            _ => write!(f, "{}+{}", seconds, increment),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Period, TimeControl};
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(TimeControl::parse("?"), Some(TimeControl::Unknown));
+        assert_eq!(TimeControl::parse("-"), Some(TimeControl::Unlimited));
+        assert_eq!(
+            TimeControl::parse("40/7200:20/3600:900+30"),
+            Some(TimeControl::Periods(vec![
+                Period::Moves {
+                    moves: 40,
+                    seconds: 7200,
+                    increment: 0,
+                },
+                Period::Moves {
+                    moves: 20,
+                    seconds: 3600,
+                    increment: 0,
+                },
+                Period::SuddenDeath {
+                    seconds: 900,
+                    increment: 30,
+                },
+            ]))
+        );
+        assert_eq!(
+            TimeControl::parse("*180"),
+            Some(TimeControl::Periods(vec![Period::Sandclock {
+                seconds: 180
+            }]))
+        );
+
+        for value in ["", "300+", "+3", "40/", "*", "5 min", "-300", "300:"] {
+            assert_eq!(TimeControl::parse(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        for value in ["?", "-", "300", "180+2", "40/5400+30:1800+30", "*60"] {
+            assert_eq!(
+                TimeControl::parse(value).map(|time_control| time_control.to_string()),
+                Some(String::from(value))
+            );
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref COMMAND: Regex = Regex::new(r"\[%(clk|emt)\s+([^\]]*?)\s*\]").unwrap();
+}
+
+/// The clock commands embedded in a comment, as in `{[%clk 0:04:58]}`.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct ClockCommands {
+    /// The comment without the commands that could be read
+    pub comment: String,
+    pub clock: Option<Duration>,
+    pub elapsed: Option<Duration>,
+}
+
+/// Takes the `[%clk]` and `[%emt]` commands out of `comment`. Commands with
+/// a malformed time are left in the comment.
+pub(crate) fn extract(comment: &str) -> ClockCommands {
+    let mut commands = ClockCommands::default();
+    let mut rest = String::new();
+    let mut end = 0;
+
+    for command in COMMAND.captures_iter(comment) {
+        let Some(time) = parse_time(&command[2]) else {
+            continue;
+        };
+
+        match &command[1] {
+            "clk" => commands.clock = Some(time),
+            _ => commands.elapsed = Some(time),
+        }
+
+        let whole = command.get(0).expect("the whole match is always present");
+        rest.push_str(&comment[end..whole.start()]);
+        rest.push(' ');
+        end = whole.end();
+    }
+    rest.push_str(&comment[end..]);
+
+    commands.comment = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+    commands
+}
+
+/// Parses a time such as `1:23:45`, `0:04:58.3` or `4:58`, `None` when it
+/// is malformed or too long to be held.
+fn parse_time(time: &str) -> Option<Duration> {
+    let mut parts = time.rsplit(':');
+    let seconds = parts.next()?;
+    let minutes = parts.next()?;
+    let hours = parts.next().unwrap_or("0");
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let is_number = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    let (whole, fraction) = seconds.split_once('.').unwrap_or((seconds, "0"));
+    if ![hours, minutes, whole, fraction].into_iter().all(is_number) {
+        return None;
+    }
+
+    let minutes = hours
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(60)?
+        .checked_add(minutes.parse().ok()?)?;
+    let seconds = minutes.checked_mul(60)?.checked_add(whole.parse().ok()?)?;
+    // Digits beyond nanoseconds are dropped
+    let nanos = format!("{:0<9}", &fraction[..fraction.len().min(9)])
+        .parse()
+        .ok()?;
+
+    Some(Duration::new(seconds, nanos))
+}
+
+/// Writes a time the way `[%clk]` commands do, as in `0:04:58`, with a
+/// fraction of a second only when there is one. The fraction is written in
+/// full, so the time reads back the same.
+pub(crate) fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    let mut formatted = format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+
+    let nanos = time.subsec_nanos();
+    if nanos > 0 {
+        let fraction = format!("{:09}", nanos);
+        formatted.push('.');
+        formatted.push_str(fraction.trim_end_matches('0'));
+    }
+
+    formatted
+}
+
+/// The comment holding the clock commands of a move, if it has any.
+pub(crate) fn comment(clock: Option<Duration>, elapsed: Option<Duration>) -> Option<String> {
+    let commands = [("clk", clock), ("emt", elapsed)]
+        .into_iter()
+        .filter_map(|(name, time)| Some(format!("[%{} {}]", name, format_time(time?))))
+        .collect::<Vec<_>>();
+
+    (!commands.is_empty()).then(|| commands.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{comment, extract, format_time, parse_time, ClockCommands};
+    use std::time::Duration;
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            extract("[%clk 0:04:58]"),
+            ClockCommands {
+                comment: String::new(),
+                clock: Some(Duration::from_secs(298)),
+                elapsed: None,
+            }
+        );
+        assert_eq!(
+            extract("Good move [%eval 0.3] [%emt 0:00:02.5] [%clk 1:00:00]"),
+            ClockCommands {
+                comment: String::from("Good move [%eval 0.3]"),
+                clock: Some(Duration::from_secs(3600)),
+                elapsed: Some(Duration::from_millis(2500)),
+            }
+        );
+        assert_eq!(
+            extract("[%clk soon]"),
+            ClockCommands {
+                comment: String::from("[%clk soon]"),
+                clock: None,
+                elapsed: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1:23:45"), Some(Duration::from_secs(5025)));
+        assert_eq!(parse_time("4:58"), Some(Duration::from_secs(298)));
+        assert_eq!(parse_time("0:00:09.1"), Some(Duration::from_millis(9100)));
+
+        assert_eq!(
+            parse_time("0:00:01.2345"),
+            Some(Duration::from_nanos(1_234_500_000))
+        );
+
+        for time in [
+            "",
+            "58",
+            "1:2:3:4",
+            "0:-1:00",
+            "0:01:",
+            "0:01:00.",
+            "999999999999999999:00:00",
+            "0:307445734561825861:00",
+        ] {
+            assert_eq!(parse_time(time), None, "{}", time);
+        }
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(Duration::from_secs(298)), "0:04:58");
+        assert_eq!(format_time(Duration::from_secs(5025)), "1:23:45");
+        assert_eq!(format_time(Duration::from_millis(9100)), "0:00:09.1");
+        assert_eq!(
+            format_time(Duration::from_nanos(1_234_500_000)),
+            "0:00:01.2345"
+        );
+
+        for time in ["1:23:45", "0:00:09.1", "0:00:01.2345", "0:00:00.000000001"] {
+            assert_eq!(parse_time(time).map(format_time).as_deref(), Some(time));
+        }
+        assert_eq!(
+            comment(Some(Duration::from_secs(60)), Some(Duration::from_secs(3))),
+            Some(String::from("[%clk 0:01:00] [%emt 0:00:03]"))
+        );
+        assert_eq!(comment(None, None), None);
+    }
+}
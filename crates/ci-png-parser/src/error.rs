@@ -0,0 +1,208 @@
+use std::{error::Error, fmt, io};
+
+use ci_core::error::FenError;
+
+use crate::png_move::NotationError;
+
+/// Position in the PGN input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// Byte offset from the start of the input
+    pub offset: usize,
+    /// Line number, counted from 1
+    pub line: usize,
+    /// Column in characters, counted from 1
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of byte `offset` of `data`.
+    pub(crate) fn new(data: &str, offset: usize) -> Location {
+        let before = &data[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Location {
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Turns a location within a game into one within the input holding it,
+    /// given the location the game starts at.
+    pub(crate) fn within(self, start: Location) -> Location {
+        Location {
+            offset: start.offset + self.offset,
+            line: start.line + self.line - 1,
+            column: match self.line {
+                1 => start.column + self.column - 1,
+                _ => self.column,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(io::Error),
+    InvalidTag {
+        location: Location,
+        token: String,
+    },
+    /// A token that cannot appear where it was found, such as a variation
+    /// before the first move
+    UnexpectedToken {
+        location: Location,
+        token: String,
+    },
+    /// A Numeric Annotation Glyph outside of the range 0 to 255
+    InvalidNag {
+        location: Location,
+        token: String,
+    },
+    InvalidMove {
+        location: Location,
+        token: String,
+        error: NotationError,
+    },
+    InvalidFen {
+        location: Location,
+        token: String,
+        error: FenError,
+    },
+}
+
+impl PgnError {
+    /// Where in the input the error was found, for errors that are not about
+    /// reading it.
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            PgnError::Io(_) => None,
+            PgnError::InvalidTag { location, .. }
+            | PgnError::UnexpectedToken { location, .. }
+            | PgnError::InvalidNag { location, .. }
+            | PgnError::InvalidMove { location, .. }
+            | PgnError::InvalidFen { location, .. } => Some(*location),
+        }
+    }
+
+    /// The text the error was found in.
+    pub fn token(&self) -> Option<&str> {
+        match self {
+            PgnError::Io(_) => None,
+            PgnError::InvalidTag { token, .. }
+            | PgnError::UnexpectedToken { token, .. }
+            | PgnError::InvalidNag { token, .. }
+            | PgnError::InvalidMove { token, .. }
+            | PgnError::InvalidFen { token, .. } => Some(token),
+        }
+    }
+
+    /// Moves the location of the error from within a game to within the
+    /// input holding it, see [`Location::within`].
+    pub(crate) fn within(mut self, start: Location) -> PgnError {
+        match &mut self {
+            PgnError::Io(_) => {}
+            PgnError::InvalidTag { location, .. }
+            | PgnError::UnexpectedToken { location, .. }
+            | PgnError::InvalidNag { location, .. }
+            | PgnError::InvalidMove { location, .. }
+            | PgnError::InvalidFen { location, .. } => *location = location.within(start),
+        }
+
+        self
+    }
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PgnError::Io(error) => write!(f, "could not read PGN: {}", error),
+            PgnError::InvalidTag { location, token } => {
+                write!(f, "{}: invalid tag pair '{}'", location, token)
+            }
+            PgnError::UnexpectedToken { location, token } => {
+                write!(f, "{}: unexpected '{}'", location, token)
+            }
+            PgnError::InvalidNag { location, token } => {
+                write!(f, "{}: invalid annotation glyph '{}'", location, token)
+            }
+            PgnError::InvalidMove {
+                location, error, ..
+            } => write!(f, "{}: invalid move: {}", location, error),
+            PgnError::InvalidFen {
+                location, error, ..
+            } => write!(f, "{}: invalid FEN tag: {}", location, error),
+        }
+    }
+}
+
+impl Error for PgnError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PgnError::Io(error) => Some(error),
+            PgnError::InvalidMove { error, .. } => Some(error),
+            PgnError::InvalidFen { error, .. } => Some(error),
+            PgnError::InvalidTag { .. }
+            | PgnError::UnexpectedToken { .. }
+            | PgnError::InvalidNag { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for PgnError {
+    fn from(error: io::Error) -> Self {
+        PgnError::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+
+    #[test]
+    fn test_location() {
+        let data = "[Event \"A\"]\n\n1. e4 Xe5\n";
+
+        assert_eq!(
+            Location::new(data, 19),
+            Location {
+                offset: 19,
+                line: 3,
+                column: 7,
+            }
+        );
+        assert_eq!(
+            Location::new(data, 0),
+            Location {
+                offset: 0,
+                line: 1,
+                column: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_within() {
+        let start = Location {
+            offset: 100,
+            line: 10,
+            column: 1,
+        };
+
+        assert_eq!(
+            Location::new("1. e4\nXe5", 6).within(start),
+            Location {
+                offset: 106,
+                line: 11,
+                column: 1,
+            }
+        );
+    }
+}
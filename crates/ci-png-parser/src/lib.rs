@@ -1,135 +1,557 @@
-use ci_core::game::{Game, GameResult, Move, Player};
-use lazy_static::lazy_static;
-use png_move::PNGMove;
-use regex::Regex;
-use std::collections::HashMap;
+use ci_core::{
+    board::{Board, Color},
+    error::ReplayError,
+    game::{Diagnostic, Game, GameResult, Player},
+    history::History,
+    tags::Tags,
+};
+use std::io::Read;
 
+mod clock;
+mod error;
 mod move_type;
+mod movetext;
 mod piece;
 pub mod png_move;
+mod reader;
+mod tag_pair;
+mod writer;
 
-lazy_static! {
-    static ref METADATA: Regex = Regex::new(r"\[(.*?)\]").unwrap();
-    static ref GAME_LINE: Regex = Regex::new(r"(\d+.\s+\S+\s+\S+)").unwrap();
-    static ref RESULT: Regex = Regex::new(r#"1/2|1-0|0-1"#).unwrap();
+pub use error::{Location, PgnError};
+pub use reader::{GameHeader, GameReader};
+pub use writer::{write, write_to};
+
+/// Parses a single PGN game.
+///
+/// # Examples
+/// ```
+/// let game = ci_png_parser::parse("[White \"Carlsen,M\"]\n\n1. e4 e5 1-0").unwrap();
+///
+/// assert_eq!(game.moves.len(), 2);
+/// assert_eq!(game.tags["White"], "Carlsen,M");
+/// ```
+pub fn parse(data: &str) -> Result<Game, PgnError> {
+    PNGParser::from_str(data).parse()
+}
+
+/// Reads a single PGN game from `reader` and parses it. Use [`GameReader`]
+/// for databases holding more than one game.
+pub fn parse_reader(mut reader: impl Read) -> Result<Game, PgnError> {
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+
+    parse(&data)
+}
+
+/// Whether `line` is an escape line, which starts with a `%` in its first
+/// column and is ignored along with the rest of the line.
+pub(crate) fn is_escape_line(line: &str) -> bool {
+    line.starts_with('%')
+}
+
+/// Appends `line` to `text` with everything but its line ending replaced by
+/// spaces, keeping the text after it at the same offsets.
+fn blank(text: &mut String, line: &str) {
+    let content = line.trim_end_matches(['\r', '\n']);
+    text.extend(content.bytes().map(|_| ' '));
+    text.push_str(&line[content.len()..]);
 }
 
 struct PNGParser<'a> {
     data: &'a str,
 }
 
-impl<'a> PNGParser<'_> {
-    // TODO: Should probably not be static, as we may want to load multiple
-    // PNGs during the lifetime of the program
-    pub fn from_str(data: &'static str) -> Self {
+impl<'a> PNGParser<'a> {
+    pub fn from_str(data: &'a str) -> Self {
         Self { data }
     }
 
-    fn parse(&self) -> Game {
-        let mut tags: HashMap<String, String> = HashMap::new();
-        let mut moves: Vec<Move> = Vec::new();
+    fn parse(&self) -> Result<Game, PgnError> {
+        let (tags, movetext, fen_offset) = self.split()?;
+
+        // Games that don't start from the initial position carry it in a FEN
+        // tag, normally accompanied by `[SetUp "1"]`
+        let turn = match tags.get("FEN") {
+            Some(fen) => match Board::from_fen(fen) {
+                Ok(board) => match board.side_to_move {
+                    Color::White => Player::White,
+                    Color::Black => Player::Black,
+                },
+                Err(error) => {
+                    return Err(PgnError::InvalidFen {
+                        location: Location::new(self.data, fen_offset),
+                        token: fen.to_string(),
+                        error,
+                    })
+                }
+            },
+            None => Player::White,
+        };
+
+        // The result is the one the movetext ends with, the tag being a
+        // fallback for games that lack a termination marker
+        let (main_line, termination) = movetext::parse(&movetext, turn)?;
+        let result = termination
+            .or_else(|| tags.result())
+            .unwrap_or(GameResult::Unknown);
+        let mut game = Game {
+            comments: main_line.comments,
+            moves: main_line.moves,
+            tags,
+            result,
+            diagnostics: Vec::new(),
+        };
+        game.diagnostics = Self::validate(&game, termination);
+
+        Ok(game)
+    }
+
+    /// Reads the tag pairs of the game and only counts the moves of its
+    /// main line, which is enough to list it in a database. The moves
+    /// themselves are not checked.
+    fn header(&self) -> Result<(Tags, usize), PgnError> {
+        let (tags, movetext, _) = self.split()?;
+
+        Ok((tags, movetext::count_plies(&movetext)))
+    }
 
-        for line in self.data.lines() {
-            if line.is_empty() {
+    /// Separates the tag pairs from the movetext. Also returns the offset of
+    /// the value of the FEN tag, to locate errors in it.
+    fn split(&self) -> Result<(Tags, String, usize), PgnError> {
+        let mut tags = Tags::new();
+        // Movetext is kept at the same byte offsets as in the input, with the
+        // tag pairs blanked out, so errors in it can be located in the input
+        let mut movetext = String::with_capacity(self.data.len());
+        let mut fen_offset = 0;
+        let mut in_movetext = false;
+
+        for line in self.data.split_inclusive('\n') {
+            if is_escape_line(line) {
+                blank(&mut movetext, line);
                 continue;
             }
 
-            match line {
-                metadata if self.is_metadata(line) => {
-                    let mut parts = metadata.split('\"');
-                    tags.insert(
-                        parts
-                            .next()
-                            .expect("Could not find tag name")
-                            .trim_start_matches('[')
-                            .trim_end_matches(']')
-                            .to_string(),
-                        parts.next().expect("Could not find tag value").to_string(),
-                    );
+            // Tag pairs only come before the movetext, a `[` in it is part of
+            // a comment or an error
+            let trimmed = line.trim();
+            if in_movetext || !trimmed.starts_with('[') {
+                in_movetext |= !trimmed.is_empty();
+                movetext.push_str(line);
+                continue;
+            }
+
+            for pair in tag_pair::parse_line(self.data, movetext.len(), line)? {
+                if pair.name == "FEN" {
+                    fen_offset = pair.offset;
                 }
-                game_line if self.is_game_line(line) => {
-                    for capture in GAME_LINE.captures_iter(game_line) {
-                        let mut parts = capture[0].split_whitespace();
-                        while let Some(_index) = parts.next() {
-                            if let (Some(white_move), Some(black_move)) =
-                                (parts.next(), parts.next())
-                            {
-                                let w_move = PNGMove::from_notation(white_move, Player::White);
-                                let b_move = PNGMove::from_notation(black_move, Player::Black);
-
-                                dbg!(white_move, w_move, black_move, b_move);
-                            }
-                        }
+                tags.insert(pair.name, pair.value);
+            }
+
+            blank(&mut movetext, line);
+        }
+
+        Ok((tags, movetext, fen_offset))
+    }
+
+    /// Replays the game to compare the check and checkmate suffixes of its
+    /// moves with the positions they lead to, and its result with the
+    /// `Result` tag and the final position.
+    fn validate(game: &Game, termination: Option<GameResult>) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        // The positions that came before are kept, a repetition can decide
+        // the game
+        let mut history = match game.start_position() {
+            Ok(board) => Some(History::new(board)),
+            Err(error) => {
+                diagnostics.push(Diagnostic::Replay(ReplayError::InvalidFen(error)));
+                None
+            }
+        };
+
+        for (index, chess_move) in game.main_line().enumerate() {
+            let Some(current) = history.as_mut() else {
+                break;
+            };
+
+            match current.apply_move(chess_move) {
+                Ok(()) => {
+                    let actual = current.board().move_result();
+
+                    if chess_move.result != actual {
+                        diagnostics.push(Diagnostic::MoveResult {
+                            ply: index + 1,
+                            notation: chess_move.result,
+                            actual,
+                        });
                     }
                 }
-                _ => {
-                    println!("No idea");
+                Err(error) => {
+                    diagnostics.push(Diagnostic::Replay(ReplayError::IllegalMove {
+                        ply: index + 1,
+                        error,
+                    }));
+                    history = None;
                 }
             }
         }
 
-        Game {
-            moves: Vec::new(),
-            tags,
-            result: self.get_result(),
-        }
-    }
-
-    fn get_result(&self) -> GameResult {
-        if let Some(result) = RESULT.captures(self.data) {
-            if result.len() > 1 {
-                println!("Found multiple results, that's a bit odd");
+        if let (Some(tag), Some(result)) = (game.tags.get("Result"), termination) {
+            if game.tags.result() != Some(result) {
+                diagnostics.push(Diagnostic::ResultTag {
+                    tag: tag.to_string(),
+                    result,
+                });
             }
+        }
 
-            if let Some(first_result) = result.get(0) {
-                return match first_result.as_str() {
-                    "1/2" => GameResult::Draw,
-                    "1-0" => GameResult::WhiteWins,
-                    "0-1" => GameResult::BlackWins,
-                    "*" | _ => GameResult::Unknown,
-                };
+        if let Some(expected) = history.as_ref().and_then(Self::decided_result) {
+            if game.result != expected {
+                diagnostics.push(Diagnostic::FinalPosition {
+                    result: game.result,
+                    expected,
+                });
             }
         }
 
-        GameResult::Unknown
+        diagnostics
     }
 
-    fn is_metadata(&self, line: &str) -> bool {
-        METADATA.is_match(line)
-    }
+    /// The result the current position of `history` ends the game with, if
+    /// any: a win for the side delivering checkmate, or a draw that needs no
+    /// claim, fivefold repetition included.
+    fn decided_result(history: &History) -> Option<GameResult> {
+        let board = history.board();
+        if board.is_checkmate() {
+            return Some(match board.side_to_move {
+                Color::White => GameResult::BlackWins,
+                Color::Black => GameResult::WhiteWins,
+            });
+        }
 
-    fn is_game_line(&self, line: &str) -> bool {
-        GAME_LINE.is_match(line)
+        history
+            .draw_reason()
+            .filter(|reason| reason.is_automatic())
+            .map(|_| GameResult::Draw)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::PNGParser;
-
-    // #[test]
-    // fn test() {
-    //     let parser = PNGParser::from_str(
-    //         r#"
-    //     [Event "F/S Return Match"]
-    //     [Site "Belgrade, Serbia JUG"]
-    //     [Date "1992.11.04"]
-    //     [Round "29"]
-    //     [White "Fischer, Robert J."]
-    //     [Black "Spassky, Boris V."]
-    //     [Result "1/2-1/2"]
-
-    //     1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 {This opening is called the Ruy Lopez.}
-    //     4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
-    //     11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
-    //     Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
-    //     23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
-    //     hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
-    //     35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
-    //     Nf2 42. g4 Bd3 43. Re6 1/2-1/2
-    //     "#,
-    //     );
-
-    //     parser.parse();
-    // }
+    use crate::{parse, parse_reader, Location, PgnError};
+    use ci_core::{
+        board::{Board, Color},
+        error::{MoveError, ReplayError},
+        file::File,
+        game::{Diagnostic, GameResult, MoveResult, MoveType, Player},
+        piece::Piece,
+        rank::Rank,
+        square::Square,
+    };
+
+    const FISCHER_SPASSKY: &str = r#"
+        [Event "F/S Return Match"]
+        [Site "Belgrade, Serbia JUG"]
+        [Date "1992.11.04"]
+        [Round "29"]
+        [White "Fischer, Robert J."]
+        [Black "Spassky, Boris V."]
+        [Result "1/2-1/2"]
+
+        1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 {This opening is called the Ruy Lopez.}
+        4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7
+        11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15. Nb1 h6 16. Bh4 c5 17. dxe5
+        Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 21. Nc4 Nxc4 22. Bxc4 Nb6
+        23. Ne5 Rae8 24. Bxf7+ Rxf7 25. Nxf7 Rxe1+ 26. Qxe1 Kxf7 27. Qe3 Qg5 28. Qxg5
+        hxg5 29. b3 Ke6 30. a3 Kd6 31. axb4 cxb4 32. Ra5 Nd5 33. f3 Bc8 34. Kf2 Bf5
+        35. Ra7 g6 36. Ra6+ Kc5 37. Ke1 Nf4 38. g3 Nxh3 39. Kd2 Kb5 40. Rd6 Kc5 41. Ra6
+        Nf2 42. g4 Bd3 43. Re6 1/2-1/2
+        "#;
+
+    #[test]
+    fn test() {
+        let game = parse(FISCHER_SPASSKY).unwrap();
+
+        assert_eq!(game.result, GameResult::Draw);
+        assert_eq!(game.tags["Event"], "F/S Return Match");
+        assert_eq!(game.tags["Black"], "Spassky, Boris V.");
+        assert_eq!(game.moves.len(), 85);
+        assert!(game.diagnostics.is_empty());
+
+        let castle = &game.moves[8].chess_move;
+        assert_eq!(castle.turn, Player::White);
+        assert_eq!(
+            castle.move_type,
+            MoveType::Castle(ci_core::game::CastleType::Kingside)
+        );
+
+        // `17. dxe5` and `Nxe4` are split over two lines
+        let knight = &game.moves[33].chess_move;
+        assert_eq!(knight.turn, Player::Black);
+        assert_eq!(knight.piece, Piece::Knight);
+        assert_eq!(knight.destination, Some((File::E, Rank::Four)));
+
+        assert_eq!(
+            game.moves[5].comments,
+            vec!["This opening is called the Ruy Lopez."]
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        let game = parse(FISCHER_SPASSKY).unwrap();
+        let (_, board) = game.positions().last().unwrap().unwrap();
+
+        assert_eq!(
+            board.piece_at(Square::E6),
+            Some((Piece::Rook, Color::White))
+        );
+        assert_eq!(
+            board.piece_at(Square::D3),
+            Some((Piece::Bishop, Color::Black))
+        );
+    }
+
+    #[test]
+    fn test_san() {
+        let game = parse(FISCHER_SPASSKY).unwrap();
+        let mut board = Board::default();
+        let mut san = Vec::new();
+
+        for chess_move in game.main_line() {
+            let resolved = board.resolve(chess_move).unwrap();
+            san.push(board.san(&resolved));
+            board.play(resolved);
+        }
+
+        assert_eq!(san[..6], ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6"]);
+        assert_eq!(san[8], "O-O");
+        assert_eq!(san[19], "Nbd7");
+        assert_eq!(san[46], "Bxf7+");
+        assert_eq!(san[84], "Re6");
+    }
+
+    #[test]
+    fn test_tags() {
+        let game = parse(FISCHER_SPASSKY).unwrap();
+
+        assert_eq!(
+            game.tags.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["Event", "Site", "Date", "Round", "White", "Black", "Result"]
+        );
+        assert_eq!(game.tags.white(), Some("Fischer, Robert J."));
+        assert_eq!(game.tags.result(), Some(GameResult::Draw));
+
+        let game = parse(
+            "[Event \"The \\\"Immortal\\\" game\"] [Site \"London\"]\n[Date \"1851.??.??\"]\n\n\
+             1. e4 {Not a tag:\n[Event \"B\"]} e5 *",
+        )
+        .unwrap();
+
+        assert_eq!(game.tags.event(), Some("The \"Immortal\" game"));
+        assert_eq!(game.tags.site(), Some("London"));
+        assert_eq!(game.tags.date().map(|date| date.year), Some(Some(1851)));
+        assert_eq!(game.tags.len(), 3);
+        assert_eq!(game.moves[0].comments, vec!["Not a tag: [Event \"B\"]"]);
+    }
+
+    #[test]
+    fn test_escape_lines() {
+        let game = parse("% Exported\n[Event \"A\"]\n% escaped\n1. e4\n%1. d4 {\n e5 *").unwrap();
+
+        assert_eq!(game.tags.len(), 1);
+        assert_eq!(game.moves.len(), 2);
+
+        // Only a `%` in the first column starts an escape line
+        let error = parse("[Event \"A\"]\n\n1. e4 % e5 *").unwrap_err();
+        assert_eq!(error.token(), Some("%"));
+        assert_eq!(error.location().map(|location| location.column), Some(7));
+    }
+
+    #[test]
+    fn test_owned_input() {
+        let data = String::from("[Event \"Casual\"]\n\n1.e4 e5 2.Nf3 1-0\n");
+        let game = parse(&data);
+        drop(data);
+
+        assert_eq!(game.unwrap().moves.len(), 3);
+    }
+
+    #[test]
+    fn test_reader() {
+        let game = parse_reader(FISCHER_SPASSKY.as_bytes()).unwrap();
+
+        assert_eq!(game.moves.len(), 85);
+    }
+
+    #[test]
+    fn test_black_to_move() {
+        let game = parse("12... Nbd7 13. Nc3 *").unwrap();
+
+        assert_eq!(game.moves[0].chess_move.turn, Player::Black);
+        assert_eq!(game.moves[1].chess_move.turn, Player::White);
+    }
+
+    #[test]
+    fn test_fen() {
+        let game =
+            parse("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 1\"]\n\n1... Kd7 2. e4 *")
+                .unwrap();
+
+        assert_eq!(game.moves[0].chess_move.turn, Player::Black);
+        assert_eq!(game.moves[1].chess_move.turn, Player::White);
+
+        assert!(matches!(
+            parse("[FEN \"8/8/8 w - - 0 1\"]\n\n*"),
+            Err(PgnError::InvalidFen { .. })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_move() {
+        assert!(matches!(
+            parse("1. e4 Xe5 1-0"),
+            Err(PgnError::InvalidMove { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let error = parse("[Event \"A\"]\n\n1. e4 e5 2. Nf3 Nc6\n3. Bb5 Xa6 1-0\n").unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(Location {
+                offset: 40,
+                line: 4,
+                column: 8,
+            })
+        );
+        assert_eq!(error.token(), Some("Xa6"));
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 8: invalid move: 'Xa6' is not valid SAN notation"
+        );
+
+        let error = parse("[White \"A\"]\n[FEN \"8/8/8 w - - 0 1\"]\n\n*").unwrap_err();
+        assert_eq!(error.location().map(|location| location.line), Some(2));
+        assert_eq!(error.location().map(|location| location.column), Some(7));
+        assert_eq!(error.token(), Some("8/8/8 w - - 0 1"));
+
+        let error = parse("[\"A\"]\n\n*").unwrap_err();
+        assert!(matches!(error, PgnError::InvalidTag { .. }));
+        assert_eq!(error.token(), Some("[\"A\"]"));
+    }
+
+    #[test]
+    fn test_move_result_diagnostics() {
+        let game = parse("1. e4 e5 2. Qh5+ Nc6 3. Bc4 Nf6 4. Qxf7+ 1-0").unwrap();
+
+        assert_eq!(
+            game.diagnostics,
+            vec![
+                Diagnostic::MoveResult {
+                    ply: 3,
+                    notation: Some(MoveResult::Check),
+                    actual: None,
+                },
+                Diagnostic::MoveResult {
+                    ply: 7,
+                    notation: Some(MoveResult::Check),
+                    actual: Some(MoveResult::Checkmate),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_result() {
+        let game = parse("[White \"Player 1-0\"]\n\n1. e4 {0-1 was expected} e5 *").unwrap();
+        assert_eq!(game.result, GameResult::Unknown);
+        assert!(game.diagnostics.is_empty());
+
+        let game = parse("[Result \"1/2-1/2\"]\n\n1. e4 e5 1/2-1/2").unwrap();
+        assert_eq!(game.result, GameResult::Draw);
+        assert!(game.diagnostics.is_empty());
+
+        // Without a termination marker the tag decides
+        let game = parse("[Result \"0-1\"]\n\n1. e4 e5").unwrap();
+        assert_eq!(game.result, GameResult::BlackWins);
+        assert!(game.diagnostics.is_empty());
+
+        assert_eq!(parse("1. e4 e5").unwrap().result, GameResult::Unknown);
+    }
+
+    #[test]
+    fn test_result_diagnostics() {
+        let game = parse("[Result \"1-0\"]\n\n1. e4 e5 0-1").unwrap();
+        assert_eq!(game.result, GameResult::BlackWins);
+        assert_eq!(
+            game.diagnostics,
+            vec![Diagnostic::ResultTag {
+                tag: String::from("1-0"),
+                result: GameResult::BlackWins,
+            }]
+        );
+
+        let game = parse("1. f3 e5 2. g4 Qh4# 1/2-1/2").unwrap();
+        assert_eq!(
+            game.diagnostics,
+            vec![Diagnostic::FinalPosition {
+                result: GameResult::Draw,
+                expected: GameResult::BlackWins,
+            }]
+        );
+        assert_eq!(
+            game.diagnostics[0].to_string(),
+            "game ends with 1/2-1/2, but its final position is 0-1"
+        );
+
+        let game = parse("[FEN \"7k/5Q2/6K1/8/8/8/8/8 b - - 0 1\"]\n\n*").unwrap();
+        assert_eq!(
+            game.diagnostics,
+            vec![Diagnostic::FinalPosition {
+                result: GameResult::Unknown,
+                expected: GameResult::Draw,
+            }]
+        );
+
+        // Fivefold repetition ends the game, threefold only allows a claim
+        let shuffle = "Nf3 Nf6 Ng1 Ng8 ";
+        let game = parse(&format!("{}*", shuffle.repeat(2))).unwrap();
+        assert!(game.diagnostics.is_empty());
+
+        let game = parse(&format!("{}*", shuffle.repeat(4))).unwrap();
+        assert_eq!(
+            game.diagnostics,
+            vec![Diagnostic::FinalPosition {
+                result: GameResult::Unknown,
+                expected: GameResult::Draw,
+            }]
+        );
+        let game = parse(&format!("{}1/2-1/2", shuffle.repeat(4))).unwrap();
+        assert!(game.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_replay_diagnostics() {
+        let game = parse("1. e4 e5 2. Ke3 1-0").unwrap();
+
+        assert!(matches!(
+            game.diagnostics[..],
+            [Diagnostic::Replay(ReplayError::IllegalMove { ply: 3, .. })]
+        ));
+
+        // The notation has to agree on whether the move captures
+        let game = parse("1. Nxf3 e5 *").unwrap();
+        assert!(matches!(
+            game.diagnostics[..],
+            [Diagnostic::Replay(ReplayError::IllegalMove {
+                ply: 1,
+                error: MoveError::CaptureMismatch { capture: true, .. },
+            })]
+        ));
+
+        let game = parse("1. e4 a6 2. e5 d5 3. exd6 e.p. *").unwrap();
+        assert!(game.diagnostics.is_empty());
+    }
 }
@@ -9,9 +9,16 @@ use ci_core::{
 };
 
 #[derive(Debug, PartialEq, Eq)]
-struct UnknownMoveTypeError;
+pub struct UnknownMoveTypeError;
 
-struct PNGMoveType(MoveType);
+#[derive(Debug)]
+pub struct PNGMoveType(MoveType);
+
+impl PNGMoveType {
+    pub fn get(self) -> MoveType {
+        self.0
+    }
+}
 
 impl FromStr for PNGMoveType {
     type Err = UnknownMoveTypeError;
@@ -21,6 +28,7 @@ impl FromStr for PNGMoveType {
             "O-O-O" => Ok(PNGMoveType(MoveType::Castle(CastleType::Queenside))),
             "O-O" => Ok(PNGMoveType(MoveType::Castle(CastleType::Kingside))),
             "x" => Ok(PNGMoveType(MoveType::Capture)),
+            "e.p." => Ok(PNGMoveType(MoveType::EnPassantCapture)),
             "=Q" => Ok(PNGMoveType(Promotion(Piece::Queen))),
             "=N" => Ok(PNGMoveType(Promotion(Piece::Knight))),
             "=B" => Ok(PNGMoveType(Promotion(Piece::Bishop))),
@@ -0,0 +1,569 @@
+use ci_core::{
+    game::{GameResult, MoveNode, MoveType, Player, Variation},
+    piece::Piece,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{clock, error::Location, png_move::PNGMove, PgnError};
+
+lazy_static! {
+    static ref MOVE_NUMBER: Regex = Regex::new(r"^\d+(\.+)").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    /// Brace or rest-of-line comment, without its delimiters
+    Comment(&'a str),
+    /// Numeric Annotation Glyph such as `$1`
+    Nag(u8),
+    StartVariation,
+    EndVariation,
+    /// Move, move number or game termination marker
+    Symbol(&'a str),
+}
+
+/// Splits movetext into tokens, each with its byte offset. Symbols end at
+/// whitespace or at the start of another token, as in `e4{comment}` or
+/// `(1...e5)`.
+struct Tokens<'a> {
+    movetext: &'a str,
+    /// The part of the movetext that is yet to be read
+    rest: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(movetext: &'a str) -> Self {
+        Self {
+            movetext,
+            rest: movetext,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (usize, Token<'a>);
+
+    fn next(&mut self) -> Option<(usize, Token<'a>)> {
+        let movetext = self.rest.trim_start();
+        let offset = self.movetext.len() - movetext.len();
+        let first = movetext.chars().next()?;
+
+        let (token, rest) = match first {
+            '{' | ';' => {
+                let end = if first == '{' { '}' } else { '\n' };
+                let text = &movetext[1..];
+
+                match text.find(end) {
+                    Some(index) => (Token::Comment(&text[..index]), &text[index + 1..]),
+                    None => (Token::Comment(text), ""),
+                }
+            }
+            '(' => (Token::StartVariation, &movetext[1..]),
+            ')' => (Token::EndVariation, &movetext[1..]),
+            _ => {
+                let end = movetext
+                    .char_indices()
+                    .skip(1)
+                    .find(|(_, c)| c.is_whitespace() || "{;()$".contains(*c))
+                    .map_or(movetext.len(), |(index, _)| index);
+                let symbol = &movetext[..end];
+
+                // NAGs that don't fit into a byte are left as symbols, to be
+                // rejected when parsing
+                let token = match symbol.strip_prefix('$').map(str::parse) {
+                    Some(Ok(nag)) => Token::Nag(nag),
+                    _ => Token::Symbol(symbol),
+                };
+
+                (token, &movetext[end..])
+            }
+        };
+
+        self.rest = rest;
+        Some((offset, token))
+    }
+}
+
+/// Parses movetext into the tree of moves it describes, `turn` being the side
+/// to make the first move. The game termination marker is returned along
+/// with the moves, only comments may follow it. Errors are located within
+/// `movetext`.
+pub(crate) fn parse(
+    movetext: &str,
+    turn: Player,
+) -> Result<(Variation, Option<GameResult>), PgnError> {
+    // The lines being read, innermost last, with the side to move in each
+    let mut lines = vec![(Variation::default(), turn)];
+    let mut termination = None;
+
+    for (offset, token) in Tokens::new(movetext) {
+        // Nothing but comments may follow the end of the game
+        if termination.is_some() {
+            let token = match token {
+                Token::Comment(_) => continue,
+                Token::Nag(nag) => format!("${}", nag),
+                Token::StartVariation => String::from("("),
+                Token::EndVariation => String::from(")"),
+                Token::Symbol(symbol) => symbol.to_string(),
+            };
+
+            return Err(PgnError::UnexpectedToken {
+                location: Location::new(movetext, offset),
+                token,
+            });
+        }
+
+        let in_variation = lines.len() > 1;
+        let (line, turn) = lines.last_mut().expect("the main line is never closed");
+
+        match token {
+            Token::Comment(comment) => match line.moves.last_mut() {
+                // Clock commands become part of the move, a comment holding
+                // nothing else is dropped like an empty one
+                Some(node) => {
+                    let commands = clock::extract(comment);
+                    node.clock = commands.clock.or(node.clock);
+                    node.elapsed = commands.elapsed.or(node.elapsed);
+
+                    if !commands.comment.is_empty() {
+                        node.comments.push(commands.comment);
+                    }
+                }
+                None => {
+                    let comment = comment.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !comment.is_empty() {
+                        line.comments.push(comment);
+                    }
+                }
+            },
+            Token::Nag(nag) => {
+                if let Some(node) = line.moves.last_mut() {
+                    node.nags.push(nag);
+                }
+            }
+            Token::StartVariation => {
+                // A variation is played instead of the last move of its line
+                let turn = line
+                    .moves
+                    .last()
+                    .map(|node| node.chess_move.turn)
+                    .ok_or_else(|| PgnError::UnexpectedToken {
+                        location: Location::new(movetext, offset),
+                        token: String::from("("),
+                    })?;
+
+                lines.push((Variation::default(), turn));
+            }
+            Token::EndVariation => {
+                if lines.len() > 1 {
+                    close_variation(&mut lines);
+                }
+            }
+            Token::Symbol(symbol) => {
+                // Move numbers may be glued to the move itself, as in `1.e4`,
+                // and three dots mark the move that follows as black's
+                let (offset, symbol) = match MOVE_NUMBER.captures(symbol) {
+                    Some(number) => {
+                        if number[1].len() == 3 {
+                            *turn = Player::Black;
+                        }
+
+                        let length = number[0].len();
+                        (offset + length, &symbol[length..])
+                    }
+                    None => (offset, symbol),
+                };
+
+                match symbol {
+                    "" => continue,
+                    // Only the main line can end the game
+                    "1-0" | "0-1" | "1/2-1/2" | "*" if in_variation => continue,
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                        termination = Some(match symbol {
+                            "1-0" => GameResult::WhiteWins,
+                            "0-1" => GameResult::BlackWins,
+                            "1/2-1/2" => GameResult::Draw,
+                            _ => GameResult::Unknown,
+                        });
+                    }
+                    // The en passant suffix may be written apart from the
+                    // capture it belongs to, as in `exd6 e.p.`
+                    suffix if is_en_passant(suffix) => {
+                        let node = line.moves.last_mut().filter(|node| {
+                            node.chess_move.piece == Piece::Pawn
+                                && matches!(
+                                    node.chess_move.move_type,
+                                    MoveType::Capture | MoveType::EnPassantCapture
+                                )
+                        });
+
+                        match node {
+                            Some(node) => node.chess_move.move_type = MoveType::EnPassantCapture,
+                            None => {
+                                return Err(PgnError::UnexpectedToken {
+                                    location: Location::new(movetext, offset),
+                                    token: suffix.to_string(),
+                                })
+                            }
+                        }
+                    }
+                    nag if nag.starts_with('$') => {
+                        return Err(PgnError::InvalidNag {
+                            location: Location::new(movetext, offset),
+                            token: nag.to_string(),
+                        })
+                    }
+                    notation => {
+                        let (notation, nag) = split_glyph(notation);
+                        let chess_move =
+                            PNGMove::from_notation(notation, *turn).map_err(|error| {
+                                PgnError::InvalidMove {
+                                    location: Location::new(movetext, offset),
+                                    token: symbol.to_string(),
+                                    error,
+                                }
+                            })?;
+                        let mut node = MoveNode::new(chess_move);
+                        node.nags.extend(nag);
+
+                        line.moves.push(node);
+                        *turn = match turn {
+                            Player::White => Player::Black,
+                            Player::Black => Player::White,
+                        };
+                    }
+                }
+            }
+        }
+    }
+
+    // Variations left open at the end of the game are closed with it
+    while lines.len() > 1 {
+        close_variation(&mut lines);
+    }
+
+    Ok((lines.remove(0).0, termination))
+}
+
+/// Adds the innermost line to the move it is an alternative to.
+fn close_variation(lines: &mut Vec<(Variation, Player)>) {
+    if let Some((variation, _)) = lines.pop() {
+        let parent = lines.last_mut().and_then(|(line, _)| line.moves.last_mut());
+
+        if let Some(parent) = parent.filter(|_| !variation.moves.is_empty()) {
+            parent.variations.push(variation);
+        }
+    }
+}
+
+/// Counts the moves of the main line without parsing them, up to the game
+/// termination marker.
+pub(crate) fn count_plies(movetext: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut plies = 0;
+
+    for (_, token) in Tokens::new(movetext) {
+        match token {
+            Token::StartVariation => depth += 1,
+            Token::EndVariation => depth = depth.saturating_sub(1),
+            Token::Symbol(symbol) if depth == 0 => {
+                let symbol = match MOVE_NUMBER.find(symbol) {
+                    Some(number) => &symbol[number.end()..],
+                    None => symbol,
+                };
+
+                match symbol {
+                    "" => {}
+                    "1-0" | "0-1" | "1/2-1/2" | "*" => break,
+                    nag if nag.starts_with('$') => {}
+                    suffix if is_en_passant(suffix) => {}
+                    _ => plies += 1,
+                }
+            }
+            Token::Comment(_) | Token::Nag(_) | Token::Symbol(_) => {}
+        }
+    }
+
+    plies
+}
+
+/// Whether `symbol` is the en passant suffix of a capture, written on its
+/// own.
+fn is_en_passant(symbol: &str) -> bool {
+    matches!(symbol, "e.p." | "e.p" | "ep." | "ep")
+}
+
+/// Splits a move suffix annotation such as `!?` off the move and returns it
+/// as its Numeric Annotation Glyph. Unknown suffixes are left on the move.
+fn split_glyph(notation: &str) -> (&str, Option<u8>) {
+    let san = notation.trim_end_matches(['!', '?']);
+
+    let nag = match &notation[san.len()..] {
+        "" => return (notation, None),
+        "!" => 1,
+        "?" => 2,
+        "!!" => 3,
+        "??" => 4,
+        "!?" => 5,
+        "?!" => 6,
+        _ => return (notation, None),
+    };
+
+    (san, Some(nag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{count_plies, parse, Token, Tokens};
+    use crate::{error::Location, PgnError};
+    use ci_core::{
+        game::{GameResult, MoveType, Player},
+        piece::Piece,
+    };
+    use std::time::Duration;
+
+    #[test]
+    fn test_tokens() {
+        let tokens = Tokens::new("1.e4{A comment} e5!? $14 (1...c5; Sicilian\n) 2. Nf3 *")
+            .map(|(_, token)| token)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Symbol("1.e4"),
+                Token::Comment("A comment"),
+                Token::Symbol("e5!?"),
+                Token::Nag(14),
+                Token::StartVariation,
+                Token::Symbol("1...c5"),
+                Token::Comment(" Sicilian"),
+                Token::EndVariation,
+                Token::Symbol("2."),
+                Token::Symbol("Nf3"),
+                Token::Symbol("*"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotations() {
+        let (line, _) = parse(
+            "{Start} 1. e4 {Best by test} e5 $1 2. Nf3!? ; King's knight\n Nc6 1-0",
+            Player::White,
+        )
+        .unwrap();
+
+        assert_eq!(line.comments, vec!["Start"]);
+        assert_eq!(line.moves.len(), 4);
+        assert_eq!(line.moves[0].comments, vec!["Best by test"]);
+        assert_eq!(line.moves[1].nags, vec![1]);
+        assert_eq!(line.moves[2].nags, vec![5]);
+        assert_eq!(line.moves[2].comments, vec!["King's knight"]);
+        assert_eq!(line.moves[3].chess_move.piece, Piece::Knight);
+
+        // Empty comments are dropped
+        let (line, _) = parse("{ } 1. e4 {} e5 {\n} *", Player::White).unwrap();
+        assert!(line.comments.is_empty());
+        assert!(line.moves.iter().all(|node| node.comments.is_empty()));
+    }
+
+    #[test]
+    fn test_invalid_nag() {
+        match parse("1. e4 $300 e5 *", Player::White) {
+            Err(PgnError::InvalidNag { location, token }) => {
+                assert_eq!(location.column, 7);
+                assert_eq!(token, "$300");
+            }
+            result => panic!("expected an invalid NAG, got {:?}", result),
+        }
+        assert!(matches!(
+            parse("1. e4 $ *", Player::White),
+            Err(PgnError::InvalidNag { .. })
+        ));
+    }
+
+    #[test]
+    fn test_clock() {
+        let (line, _) = parse(
+            "1. e4 {[%clk 0:04:58]} e5 { [%clk 0:04:57] [%emt 0:00:03] } 2. Nf3 {Fast [%clk 0:04:55]} {} *",
+            Player::White,
+        )
+        .unwrap();
+
+        assert_eq!(line.moves[0].clock, Some(Duration::from_secs(298)));
+        assert!(line.moves[0].comments.is_empty());
+        assert_eq!(line.moves[1].clock, Some(Duration::from_secs(297)));
+        assert_eq!(line.moves[1].elapsed, Some(Duration::from_secs(3)));
+        assert_eq!(line.moves[2].clock, Some(Duration::from_secs(295)));
+        assert_eq!(line.moves[2].comments, vec!["Fast"]);
+
+        // A time too long to be held is left in the comment
+        let (line, _) = parse("1. e4 {[%clk 999999999999999999:00:00]} *", Player::White).unwrap();
+        assert_eq!(line.moves[0].clock, None);
+        assert_eq!(
+            line.moves[0].comments,
+            vec!["[%clk 999999999999999999:00:00]"]
+        );
+    }
+
+    #[test]
+    fn test_variations() {
+        let (line, _) = parse(
+            "1. e4 e5 (1... c5 2. Nf3 (2. c3) d6) (1... e6 {French}) 2. Nf3 *",
+            Player::White,
+        )
+        .unwrap();
+
+        assert_eq!(line.moves.len(), 3);
+
+        let variations = &line.moves[1].variations;
+        assert_eq!(variations.len(), 2);
+        assert_eq!(variations[0].moves.len(), 3);
+        assert_eq!(variations[0].moves[0].chess_move.turn, Player::Black);
+        assert_eq!(variations[0].moves[1].variations[0].moves.len(), 1);
+        assert_eq!(variations[1].moves[0].comments, vec!["French"]);
+        assert_eq!(line.moves[2].chess_move.turn, Player::White);
+    }
+
+    #[test]
+    fn test_unbalanced_variations() {
+        let (line, _) = parse("1. e4 e5 (1... c5 2. Nf3 *", Player::White).unwrap();
+        assert_eq!(line.moves[1].variations[0].moves.len(), 2);
+
+        let (line, _) = parse("1. e4 ) e5 *", Player::White).unwrap();
+        assert_eq!(line.moves.len(), 2);
+
+        assert!(matches!(
+            parse("(1. d4) 1. e4 *", Player::White),
+            Err(PgnError::UnexpectedToken { .. })
+        ));
+    }
+
+    #[test]
+    fn test_offsets() {
+        let offsets = Tokens::new("1. e4 {Good}\n e5")
+            .map(|(offset, _)| offset)
+            .collect::<Vec<_>>();
+
+        assert_eq!(offsets, vec![0, 3, 6, 14]);
+
+        match parse("1. e4 e5\n2.Nf3 Xc6 *", Player::White) {
+            Err(PgnError::InvalidMove {
+                location, token, ..
+            }) => {
+                assert_eq!(
+                    location,
+                    Location {
+                        offset: 15,
+                        line: 2,
+                        column: 7,
+                    }
+                );
+                assert_eq!(token, "Xc6");
+            }
+            result => panic!("expected an invalid move, got {:?}", result),
+        }
+
+        match parse("1.e4 e5 2.Xf3 *", Player::White) {
+            Err(error) => assert_eq!(error.location().map(|location| location.column), Some(11)),
+            Ok(_) => panic!("expected an invalid move"),
+        }
+    }
+
+    #[test]
+    fn test_count_plies() {
+        assert_eq!(count_plies(""), 0);
+        assert_eq!(count_plies("1-0"), 0);
+        assert_eq!(
+            count_plies(
+                "{Start} 1.e4 e5 $1 (1... c5 2. Nf3 (2. c3)) 2. Nf3 {A} ; B\n Nc6 * 3. Bb5"
+            ),
+            4
+        );
+        assert_eq!(count_plies("12... Nbd7 13. Xx9 *"), 2);
+    }
+
+    #[test]
+    fn test_en_passant() {
+        for movetext in [
+            "1. e4 a6 2. e5 d5 3. exd6 e.p. *",
+            "1. e4 a6 2. e5 d5 3. exd6 ep *",
+            "1. e4 a6 2. e5 d5 3. exd6e.p. *",
+        ] {
+            let (line, _) = parse(movetext, Player::White).unwrap();
+
+            assert_eq!(line.moves.len(), 5, "{}", movetext);
+            assert_eq!(
+                line.moves[4].chess_move.move_type,
+                MoveType::EnPassantCapture,
+                "{}",
+                movetext
+            );
+            assert_eq!(count_plies(movetext), 5);
+        }
+
+        for movetext in [
+            "e.p. 1. e4 *",
+            "1. e4 e.p. *",
+            "1. e4 d5 2. Bb5 a6 3. Bxa6 e.p. *",
+        ] {
+            assert!(
+                matches!(
+                    parse(movetext, Player::White),
+                    Err(PgnError::UnexpectedToken { .. })
+                ),
+                "{}",
+                movetext
+            );
+        }
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let (line, _) = parse("1. e4! e5? 2. Nf3!! Nc6?? 3. Bb5 a6?! *", Player::White).unwrap();
+        let nags = line
+            .moves
+            .iter()
+            .map(|node| node.nags.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            nags,
+            vec![vec![1], vec![2], vec![3], vec![4], vec![], vec![6]]
+        );
+        assert!(parse("1. e4!!! *", Player::White).is_err());
+    }
+
+    #[test]
+    fn test_termination() {
+        let termination = |movetext| parse(movetext, Player::White).unwrap().1;
+
+        assert_eq!(termination("1. e4 e5 1-0"), Some(GameResult::WhiteWins));
+        assert_eq!(termination("1. e4 0-1"), Some(GameResult::BlackWins));
+        assert_eq!(termination("1/2-1/2"), Some(GameResult::Draw));
+        assert_eq!(termination("1. e4 *"), Some(GameResult::Unknown));
+        assert_eq!(termination("1. e4 e5"), None);
+
+        // Results in comments or variations are not the result of the game
+        assert_eq!(
+            termination("1. e4 {1-0 was expected} (1. d4 0-1) e5 1/2-1/2"),
+            Some(GameResult::Draw)
+        );
+
+        // Only comments may follow the marker
+        assert_eq!(
+            termination("1. e4 e5 1-0 {White resigned later}"),
+            Some(GameResult::WhiteWins)
+        );
+        assert!(matches!(
+            parse("1. e4 e5 1-0 2. Nf3 Nc6", Player::White),
+            Err(PgnError::UnexpectedToken { token, .. }) if token == "2."
+        ));
+        assert!(matches!(
+            parse("1. e4 * (1. d4)", Player::White),
+            Err(PgnError::UnexpectedToken { token, .. }) if token == "("
+        ));
+    }
+}
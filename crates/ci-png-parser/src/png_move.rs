@@ -1,68 +1,250 @@
-use std::str::FromStr;
+use std::{error::Error, fmt, str::FromStr};
 
 use ci_core::{
     file::File,
-    game::{Move, Player},
+    game::{CastleType, Move, MoveResult, MoveType, Player},
+    piece::Piece,
     rank::Rank,
 };
+use lazy_static::lazy_static;
 use regex::Regex;
 
-use crate::piece::PNGPiece;
+use crate::{move_type::PNGMoveType, piece::PNGPiece};
 
-pub struct PNGMove(Move);
+lazy_static! {
+    static ref SAN: Regex = Regex::new(
+        r"(?x)
+        ^(?:
+            (?P<castle>O-O-O|O-O|0-0-0|0-0)
+            |
+            (?P<piece>[KQRBNP])?
+            (?P<origin_file>[a-h])?
+            (?P<origin_rank>[1-8])?
+            (?P<capture>[x:])?
+            (?P<file>[a-h])
+            (?P<rank>[1-8])
+            (?:=?(?P<promotion>[QRBN]))?
+            (?:\s*(?P<en_passant>e\.?p\.?))?
+        )
+        (?P<result>[+\#])?
+        (?P<glyph>[!?]{1,2})?$"
+    )
+    .unwrap();
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NotationError {
+    Empty,
+    Malformed(String),
+    IllegalPromotion(String),
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotationError::Empty => f.write_str("empty move notation"),
+            NotationError::Malformed(notation) => {
+                write!(f, "'{}' is not valid SAN notation", notation)
+            }
+            NotationError::IllegalPromotion(notation) => {
+                write!(
+                    f,
+                    "'{}' promotes a piece that is not a pawn on its last rank",
+                    notation
+                )
+            }
+        }
+    }
+}
+
+impl Error for NotationError {}
+
+pub struct PNGMove;
 
 impl PNGMove {
-    // ! Transforms PNG SAN notation to concrete move data.
-    // !
-    // ! # Examples
-    // ! ```
-    // ! use ci_png_parser::{png_move::PNGMove};
-    // ! use ci_core::{file::File, game::Move, game::MoveType, game::Player, piece::Piece, rank::Rank};
-    // !
-    // ! let notation = "e4";
-    // ! let chess_move = PNGMove::from_notation(notation, Player::White);
-    // !
-    // ! assert!(chess_move == Some(Move {
-    // !   piece: Piece::Pawn,
-    // !   turn: Player::White,
-    // !   move_type: MoveType::Move,
-    // !   destination: Some((File::E, Rank::Four)),
-    // !   result: None,
-    // ! }))
-    // ! ```
-    pub fn from_notation(notation: &str, turn: Player) -> Option<Move> {
-        let re = Regex::new("^([KQRBNP])?([a-h])([1-8])$").unwrap();
-        if let Some(captures) = re.captures(notation) {
-            let [_match, piece, file, rank]: &[&str] = &captures
-                .iter()
-                .map(|capture| capture.as_ref().map(|m| m.as_str()).unwrap_or(""))
-                .collect::<Vec<&str>>()[..] else {
-                    panic!("Did not receive valid PNG SAN notation {}", notation);
-                };
-
-            dbg!(piece, file, rank);
-            // TODO: castling, captures, checks, checkmates, as well as skip the result of the game
-            // idea: do parse the result of the game on move level, instead of game level, keeps the games spoiler free!
-            return Some(Move {
-                piece: PNGPiece::from_str(piece).expect("Unknown piece").get(),
+    /// Transforms PNG SAN notation to concrete move data.
+    ///
+    /// Check and checkmate suffixes are kept on the move, annotation glyphs
+    /// such as `!?` are accepted but not stored.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_png_parser::png_move::PNGMove;
+    /// use ci_core::{file::File, game::Move, game::MoveType, game::Player, piece::Piece, rank::Rank};
+    ///
+    /// let notation = "e4";
+    /// let chess_move = PNGMove::from_notation(notation, Player::White);
+    ///
+    /// assert_eq!(chess_move, Ok(Move {
+    ///   piece: Piece::Pawn,
+    ///   turn: Player::White,
+    ///   origin: None,
+    ///   move_type: MoveType::Move,
+    ///   destination: Some((File::E, Rank::Four)),
+    ///   result: None,
+    /// }))
+    /// ```
+    pub fn from_notation(notation: &str, turn: Player) -> Result<Move, NotationError> {
+        let notation = notation.trim();
+        if notation.is_empty() {
+            return Err(NotationError::Empty);
+        }
+
+        let captures = SAN
+            .captures(notation)
+            .ok_or_else(|| NotationError::Malformed(notation.to_string()))?;
+
+        let result = captures.name("result").map(|result| match result.as_str() {
+            "#" => MoveResult::Checkmate,
+            _ => MoveResult::Check,
+        });
+
+        if let Some(castle) = captures.name("castle") {
+            let castle = castle.as_str().replace('0', "O");
+
+            return Ok(Move {
+                piece: Piece::King,
                 turn,
                 origin: None,
-                destination: Some((
-                    File::from_str(file).expect("Unknown file"),
-                    Rank::from_str(rank).expect("Unknown rank"),
-                )),
-                move_type: ci_core::game::MoveType::Move,
-                result: None,
+                destination: None,
+                move_type: Self::move_type(&castle, notation)?,
+                result,
             });
         }
 
-        None
+        // Every branch of the expression other than castling captures a piece,
+        // a destination file and a destination rank
+        let text = |name: &str| captures.name(name).map(|m| m.as_str());
+        let piece = PNGPiece::from_str(text("piece").unwrap_or_default())
+            .map_err(|_| NotationError::Malformed(notation.to_string()))?
+            .get();
+        let destination = (
+            File::from_str(text("file").unwrap_or_default())
+                .ok_or_else(|| NotationError::Malformed(notation.to_string()))?,
+            Rank::from_str(text("rank").unwrap_or_default())
+                .ok_or_else(|| NotationError::Malformed(notation.to_string()))?,
+        );
+
+        let origin_file = text("origin_file").and_then(File::from_str);
+        let origin_rank = text("origin_rank").and_then(Rank::from_str);
+        let origin = match (origin_file, origin_rank) {
+            (None, None) => None,
+            hint => Some(hint),
+        };
+
+        let move_type = if let Some(promotion) = text("promotion") {
+            let last_rank = match turn {
+                Player::White => Rank::Eight,
+                Player::Black => Rank::One,
+            };
+
+            if piece != Piece::Pawn || destination.1 != last_rank {
+                return Err(NotationError::IllegalPromotion(notation.to_string()));
+            }
+
+            Self::move_type(&format!("={}", promotion), notation)?
+        } else if text("en_passant").is_some() {
+            if piece != Piece::Pawn || text("capture").is_none() {
+                return Err(NotationError::Malformed(notation.to_string()));
+            }
+
+            Self::move_type("e.p.", notation)?
+        } else if text("capture").is_some() {
+            Self::move_type("x", notation)?
+        } else {
+            MoveType::Move
+        };
+
+        // A pawn can only reach another file by capturing
+        if piece == Piece::Pawn && text("capture").is_none() && origin_file.is_some() {
+            return Err(NotationError::Malformed(notation.to_string()));
+        }
+
+        Ok(Move {
+            piece,
+            turn,
+            origin,
+            destination: Some(destination),
+            move_type,
+            result,
+        })
+    }
+
+    /// Writes a move back to SAN notation from the information it carries,
+    /// without looking at the position it is played in. Use
+    /// [`Board::san`](ci_core::board::Board::san) for notation with minimal
+    /// disambiguation.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_png_parser::png_move::PNGMove;
+    /// use ci_core::game::Player;
+    ///
+    /// let chess_move = PNGMove::from_notation("Nbxd7+", Player::White).unwrap();
+    ///
+    /// assert_eq!(PNGMove::to_notation(&chess_move), "Nbxd7+");
+    /// ```
+    pub fn to_notation(chess_move: &Move) -> String {
+        let mut notation = match chess_move.move_type {
+            MoveType::Castle(CastleType::Kingside) => String::from("O-O"),
+            MoveType::Castle(CastleType::Queenside) => String::from("O-O-O"),
+            _ => {
+                let mut notation = String::new();
+
+                if chess_move.piece != Piece::Pawn {
+                    notation.push(chess_move.piece.to_char());
+                }
+                if let Some((file, rank)) = chess_move.origin {
+                    notation.extend(file.map(|file| file.to_str()));
+                    notation.extend(rank.map(|rank| rank.to_str()));
+                }
+                // Promotions don't record captures, but a pawn changing files
+                // always captures
+                let changes_file = chess_move.piece == Piece::Pawn
+                    && matches!(
+                        (chess_move.origin, chess_move.destination),
+                        (Some((Some(from), _)), Some((to, _))) if from != to
+                    );
+                if changes_file
+                    || matches!(
+                        chess_move.move_type,
+                        MoveType::Capture | MoveType::EnPassantCapture
+                    )
+                {
+                    notation.push('x');
+                }
+                if let Some((file, rank)) = chess_move.destination {
+                    notation.push_str(file.to_str());
+                    notation.push_str(rank.to_str());
+                }
+                if let MoveType::Promotion(piece) = chess_move.move_type {
+                    notation.push('=');
+                    notation.push(piece.to_char());
+                }
+
+                notation
+            }
+        };
+
+        match chess_move.result {
+            Some(MoveResult::Check) => notation.push('+'),
+            Some(MoveResult::Checkmate) => notation.push('#'),
+            None => {}
+        }
+
+        notation
+    }
+
+    fn move_type(token: &str, notation: &str) -> Result<MoveType, NotationError> {
+        PNGMoveType::from_str(token)
+            .map(PNGMoveType::get)
+            .map_err(|_| NotationError::Malformed(notation.to_string()))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::PNGMove;
+    use super::{NotationError, PNGMove};
     use ci_core::{
         file::File,
         game::CastleType,
@@ -78,7 +260,7 @@ mod tests {
         [
             (
                 "e4", // Pawn moves to e4
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Pawn,
                     turn: Player::White,
                     move_type: MoveType::Move,
@@ -89,7 +271,7 @@ mod tests {
             ),
             (
                 "Re6", // Rook moves to e6
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Rook,
                     turn: Player::White,
                     move_type: MoveType::Move,
@@ -100,18 +282,18 @@ mod tests {
             ),
             (
                 "exd5", // Pawn on the E file captures piece on d5
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Pawn,
                     turn: Player::White,
                     move_type: MoveType::Capture,
-                    origin: None,
+                    origin: Some((Some(File::E), None)),
                     destination: Some((File::D, Rank::Five)),
                     result: None,
                 }),
             ),
             (
                 "Rdf8", // Rook on the D file moves to f8, this is required if both rooks are eligible to make the move to f8
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Rook,
                     turn: Player::White,
                     move_type: MoveType::Move,
@@ -120,9 +302,31 @@ mod tests {
                     result: None,
                 }),
             ),
+            (
+                "N1f3", // Knight on the first rank moves to f3
+                Ok(Move {
+                    piece: Piece::Knight,
+                    turn: Player::White,
+                    move_type: MoveType::Move,
+                    origin: Some((None, Some(Rank::One))),
+                    destination: Some((File::F, Rank::Three)),
+                    result: None,
+                }),
+            ),
+            (
+                "Qh4e1", // Queen on h4 moves to e1, required when three queens can reach e1
+                Ok(Move {
+                    piece: Piece::Queen,
+                    turn: Player::White,
+                    move_type: MoveType::Move,
+                    origin: Some((Some(File::H), Some(Rank::Four))),
+                    destination: Some((File::E, Rank::One)),
+                    result: None,
+                }),
+            ),
             (
                 "exd6 e.p", // Pawn on the E file captures en passant and moves to d6
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Pawn,
                     turn: Player::White,
                     move_type: MoveType::EnPassantCapture,
@@ -133,7 +337,7 @@ mod tests {
             ),
             (
                 "O-O", // Castle kingside
-                Some(Move {
+                Ok(Move {
                     piece: Piece::King,
                     turn: Player::White,
                     move_type: MoveType::Castle(CastleType::Kingside),
@@ -144,7 +348,7 @@ mod tests {
             ),
             (
                 "O-O-O", // Castle queenside
-                Some(Move {
+                Ok(Move {
                     piece: Piece::King,
                     turn: Player::White,
                     move_type: MoveType::Castle(CastleType::Queenside),
@@ -155,7 +359,7 @@ mod tests {
             ),
             (
                 "Rxe1+", // Rook captures on E1 and puts opponent in check position
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Rook,
                     turn: Player::White,
                     move_type: MoveType::Capture,
@@ -166,7 +370,7 @@ mod tests {
             ),
             (
                 "e8=Q", // Pawn moves to E8 and promotes to queen
-                Some(Move {
+                Ok(Move {
                     piece: Piece::Pawn,
                     turn: Player::White,
                     move_type: MoveType::Promotion(Piece::Queen),
@@ -176,9 +380,20 @@ mod tests {
                 }),
             ),
             (
-                "Kxe4#", // Knight captures on e4 and checkmates the opponent
-                Some(Move {
-                    piece: Piece::Knight,
+                "dxc8=N+", // Pawn on the D file captures on c8, promotes to knight and gives check
+                Ok(Move {
+                    piece: Piece::Pawn,
+                    turn: Player::White,
+                    move_type: MoveType::Promotion(Piece::Knight),
+                    origin: Some((Some(File::D), None)),
+                    destination: Some((File::C, Rank::Eight)),
+                    result: Some(MoveResult::Check),
+                }),
+            ),
+            (
+                "Kxe4#", // King captures on e4 and checkmates the opponent
+                Ok(Move {
+                    piece: Piece::King,
                     turn: Player::White,
                     move_type: MoveType::Capture,
                     origin: None,
@@ -186,10 +401,75 @@ mod tests {
                     result: Some(MoveResult::Checkmate),
                 }),
             ),
+            (
+                "Nf3!?", // Knight moves to f3, annotated as an interesting move
+                Ok(Move {
+                    piece: Piece::Knight,
+                    turn: Player::White,
+                    move_type: MoveType::Move,
+                    origin: None,
+                    destination: Some((File::F, Rank::Three)),
+                    result: None,
+                }),
+            ),
+            ("", Err(NotationError::Empty)),
+            ("Xe4", Err(NotationError::Malformed(String::from("Xe4")))),
+            ("e9", Err(NotationError::Malformed(String::from("e9")))),
+            (
+                "Ne8=Q",
+                Err(NotationError::IllegalPromotion(String::from("Ne8=Q"))),
+            ),
+            (
+                "e5=Q",
+                Err(NotationError::IllegalPromotion(String::from("e5=Q"))),
+            ),
         ]
-        .map(|(notation, expected)| {
+        .into_iter()
+        .for_each(|(notation, expected)| {
             let result = PNGMove::from_notation(notation, ci_core::game::Player::White);
-            assert_eq!(result, expected);
+            assert_eq!(result, expected, "{}", notation);
         });
     }
+
+    #[test]
+    pub fn test_to_notation() {
+        [
+            "e4", "exd5", "Rdf8", "N1f3", "Qh4e1", "O-O", "O-O-O", "Rxe1+", "dxc8=N+", "Kxe4#",
+        ]
+        .into_iter()
+        .for_each(|notation| {
+            let chess_move = PNGMove::from_notation(notation, Player::White).unwrap();
+            assert_eq!(PNGMove::to_notation(&chess_move), notation);
+        });
+
+        let chess_move = PNGMove::from_notation("exd6 e.p.", Player::White).unwrap();
+        assert_eq!(PNGMove::to_notation(&chess_move), "exd6");
+    }
+
+    #[test]
+    pub fn test_black() {
+        assert_eq!(
+            PNGMove::from_notation("0-0-0", Player::Black),
+            Ok(Move {
+                piece: Piece::King,
+                turn: Player::Black,
+                move_type: MoveType::Castle(CastleType::Queenside),
+                origin: None,
+                destination: None,
+                result: None,
+            })
+        );
+
+        assert_eq!(
+            PNGMove::from_notation("a1=R#", Player::Black),
+            Ok(Move {
+                piece: Piece::Pawn,
+                turn: Player::Black,
+                move_type: MoveType::Promotion(Piece::Rook),
+                origin: None,
+                destination: Some((File::A, Rank::One)),
+                result: Some(MoveResult::Checkmate),
+            })
+        );
+    }
 }
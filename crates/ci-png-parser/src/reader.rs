@@ -0,0 +1,436 @@
+use std::io::BufRead;
+
+use ci_core::{game::Game, tags::Tags};
+
+use crate::{error::Location, is_escape_line, PNGParser, PgnError};
+
+/// The tag pairs of a game in a PGN database, with where to find the game,
+/// see [`GameReader::next_header`].
+#[derive(Debug, Clone)]
+pub struct GameHeader {
+    pub tags: Tags,
+    /// Number of moves of the main line
+    pub plies: usize,
+    /// Where the game starts in the input
+    pub location: Location,
+}
+
+/// Iterates over the games of a PGN database, one game at a time.
+///
+/// Only the text of the game that is currently being parsed is kept in
+/// memory, so arbitrarily large databases can be processed.
+///
+/// # Examples
+/// ```
+/// use ci_png_parser::GameReader;
+///
+/// let pgn = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 0-1\n";
+/// let games = GameReader::new(pgn.as_bytes())
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(games.len(), 2);
+/// assert_eq!(games[1].tags["Event"], "B");
+/// ```
+///
+/// Errors are located within the whole input rather than within the game.
+pub struct GameReader<R: BufRead> {
+    reader: R,
+    line: Vec<u8>,
+    /// Location of the next line to be read
+    location: Location,
+    /// Tag line which marked the end of the previous game and the start of
+    /// the next one, with its location
+    pending: Option<(Location, String)>,
+    lenient: bool,
+    errors: Vec<PgnError>,
+}
+
+impl<R: BufRead> GameReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            location: Location {
+                offset: 0,
+                line: 1,
+                column: 1,
+            },
+            pending: None,
+            lenient: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Skips games that cannot be parsed instead of returning their errors,
+    /// which are kept in [`GameReader::errors`]. An `[Event` tag always starts
+    /// a new game, even inside a comment left open by a broken game.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_png_parser::GameReader;
+    ///
+    /// let pgn = "[Event \"A\"]\n\n1. e4 {Unclosed 1-0\n\n[Event \"B\"]\n\n1. d4 Xd5 0-1\n\n\
+    ///            [Event \"C\"]\n\n1. c4 1-0\n";
+    /// let mut reader = GameReader::new(pgn.as_bytes()).lenient();
+    /// let games = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    ///
+    /// assert_eq!(games.len(), 2);
+    /// assert_eq!(games[1].tags["Event"], "C");
+    /// assert_eq!(reader.errors().len(), 1);
+    /// ```
+    pub fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Reads the input as the part of a larger one that starts at
+    /// `location`, so errors and headers are located within the larger
+    /// input. This allows reading a game on its own from a
+    /// [`GameHeader::location`].
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_png_parser::GameReader;
+    ///
+    /// let pgn = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 0-1\n";
+    /// let mut reader = GameReader::new(pgn.as_bytes());
+    /// reader.next_header();
+    /// let start = reader.next_header().unwrap().unwrap().location;
+    ///
+    /// let game = GameReader::new(&pgn.as_bytes()[start.offset..])
+    ///     .starting_at(start)
+    ///     .next()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(game.tags["Event"], "B");
+    /// ```
+    pub fn starting_at(mut self, location: Location) -> Self {
+        self.location = location;
+        self
+    }
+
+    /// Errors of the games skipped in lenient mode.
+    pub fn errors(&self) -> &[PgnError] {
+        &self.errors
+    }
+
+    /// Reads the tag pairs of the next game and counts its moves, without
+    /// parsing them. This makes listing the games of a large database much
+    /// faster than reading them in full, only errors in tag pairs are found.
+    ///
+    /// # Examples
+    /// ```
+    /// use ci_png_parser::GameReader;
+    ///
+    /// let pgn = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 0-1\n";
+    /// let mut reader = GameReader::new(pgn.as_bytes());
+    /// let header = reader.next_header().unwrap().unwrap();
+    ///
+    /// assert_eq!(header.tags["Event"], "A");
+    /// assert_eq!(header.plies, 2);
+    /// assert_eq!(reader.next_header().unwrap().unwrap().location.line, 5);
+    /// assert!(reader.next_header().is_none());
+    /// ```
+    pub fn next_header(&mut self) -> Option<Result<GameHeader, PgnError>> {
+        self.read(|parser| parser.header()).map(|result| {
+            result.map(|(location, (tags, plies))| GameHeader {
+                tags,
+                plies,
+                location,
+            })
+        })
+    }
+
+    /// Reads the next game with `parse`, along with the location it starts
+    /// at. In lenient mode, games it fails on are skipped.
+    fn read<T>(
+        &mut self,
+        parse: impl Fn(&PNGParser) -> Result<T, PgnError>,
+    ) -> Option<Result<(Location, T), PgnError>> {
+        loop {
+            let (start, data) = match self.next_game() {
+                Ok(Some(game)) => game,
+                Ok(None) => return None,
+                Err(error) => return Some(Err(error)),
+            };
+
+            match parse(&PNGParser::from_str(&data)) {
+                Ok(value) => return Some(Ok((start, value))),
+                Err(error) if self.lenient => self.errors.push(error.within(start)),
+                Err(error) => return Some(Err(error.within(start))),
+            }
+        }
+    }
+
+    /// Reads the next line, decoding invalid UTF-8 (common in older Latin-1
+    /// encoded databases) lossily. Returns `None` at the end of the input.
+    fn read_line(&mut self) -> Result<Option<String>, PgnError> {
+        self.line.clear();
+
+        if self.reader.read_until(b'\n', &mut self.line)? == 0 {
+            return Ok(None);
+        }
+
+        self.location.offset += self.line.len();
+        self.location.line += 1;
+
+        Ok(Some(String::from_utf8_lossy(&self.line).into_owned()))
+    }
+
+    /// Collects the text of the next game. A game ends where the tag pairs of
+    /// the next one start, or at the end of the input. Returns the text with
+    /// the location it starts at.
+    fn next_game(&mut self) -> Result<Option<(Location, String)>, PgnError> {
+        let (start, mut game) = self
+            .pending
+            .take()
+            .unwrap_or((self.location, String::new()));
+        let mut in_movetext = false;
+        let mut in_comment = false;
+
+        loop {
+            let location = self.location;
+            let Some(line) = self.read_line()? else {
+                break;
+            };
+            let trimmed = line.trim();
+            let starts_game = !in_comment || (self.lenient && trimmed.starts_with("[Event "));
+
+            if is_escape_line(&line) {
+                // Left for the parser to skip
+            } else if starts_game && trimmed.starts_with('[') {
+                if in_movetext {
+                    self.pending = Some((location, line));
+                    break;
+                }
+
+                in_comment = false;
+            } else if !trimmed.is_empty() {
+                in_movetext = true;
+                in_comment = ends_in_comment(&line, in_comment);
+            }
+
+            game.push_str(&line);
+        }
+
+        if game.trim().is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some((start, game)))
+    }
+}
+
+/// Whether a brace comment is left open at the end of `line`, `in_comment`
+/// telling whether one was open at its start. Brace comments don't nest and
+/// end at the first `}`, a `;` comment runs to the end of the line.
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    for c in line.chars() {
+        match (in_comment, c) {
+            (true, '}') => in_comment = false,
+            (false, '{') => in_comment = true,
+            (false, ';') => break,
+            _ => {}
+        }
+    }
+
+    in_comment
+}
+
+impl<R: BufRead> Iterator for GameReader<R> {
+    type Item = Result<Game, PgnError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read(|parser| parser.parse())
+            .map(|result| result.map(|(_, game)| game))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameReader;
+    use crate::Location;
+    use ci_core::game::GameResult;
+
+    const DATABASE: &str = r#"[Event "WCh 2021"]
+[White "Carlsen,M"]
+[Black "Nepomniachtchi,I"]
+[Result "1/2-1/2"]
+
+1. e4 e5 2. Nf3 Nf6 3. Nxe5 d6 4. Nf3 Nxe4 5. d4 d5 6. Bd3 Bd6 7. O-O O-O 8. c4
+c6 1/2-1/2
+
+[Event "Titled Tuesday 30th Nov"]
+[White "Rostovtsev,Dmitry"]
+[Black "Moore,Nicholas"]
+[Result "0-1"]
+
+0-1
+
+[Event "Casual"]
+[Result "1-0"]
+1. e4 {A comment that
+[spans] several lines} e5 2. Qh5 Nc6 3. Bc4 Nf6 4. Qxf7# 1-0
+"#;
+
+    #[test]
+    fn test_games() {
+        let games = GameReader::new(DATABASE.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 3);
+
+        assert_eq!(games[0].tags["White"], "Carlsen,M");
+        assert_eq!(games[0].moves.len(), 16);
+        assert_eq!(games[0].result, GameResult::Draw);
+
+        assert_eq!(games[1].tags["Event"], "Titled Tuesday 30th Nov");
+        assert!(games[1].moves.is_empty());
+        assert_eq!(games[1].result, GameResult::BlackWins);
+
+        assert_eq!(games[2].moves.len(), 7);
+        assert_eq!(games[2].result, GameResult::WhiteWins);
+    }
+
+    #[test]
+    fn test_headers() {
+        let mut reader = GameReader::new(DATABASE.as_bytes());
+        let headers = std::iter::from_fn(|| reader.next_header())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let games = GameReader::new(DATABASE.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(headers.len(), 3);
+        assert_eq!(
+            headers
+                .iter()
+                .map(|header| header.plies)
+                .collect::<Vec<_>>(),
+            [16, 0, 7]
+        );
+        assert_eq!(headers[1].tags["Event"], "Titled Tuesday 30th Nov");
+
+        // Each game can be read on its own from where its header was found
+        for (header, game) in headers.iter().zip(&games) {
+            let start = header.location;
+            let mut reader =
+                GameReader::new(&DATABASE.as_bytes()[start.offset..]).starting_at(start);
+
+            let read = reader.next().unwrap().unwrap();
+            assert_eq!(read.tags, game.tags);
+            assert_eq!(read.moves, game.moves);
+            assert_eq!(read.moves.len(), header.plies);
+        }
+
+        let pgn = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 Xd5\n";
+        let mut reader = GameReader::new(pgn.as_bytes());
+        reader.next_header();
+        let start = reader.next_header().unwrap().unwrap().location;
+        assert_eq!(start.line, 5);
+
+        // Errors are located within the whole input
+        let error = GameReader::new(&pgn.as_bytes()[start.offset..])
+            .starting_at(start)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(error.location().map(|location| location.line), Some(7));
+    }
+
+    #[test]
+    fn test_invalid_game_does_not_stop_reader() {
+        let pgn = "[Event \"A\"]\n\n1. e4 Xe5 1-0\n\n[Event \"B\"]\n\n1. d4 0-1\n";
+        let games = GameReader::new(pgn.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(games.len(), 2);
+        assert!(games[0].is_err());
+        assert_eq!(games[1].as_ref().unwrap().tags["Event"], "B");
+    }
+
+    #[test]
+    fn test_error_location() {
+        let pgn = "[Event \"A\"]\n\n1. e4 e5 1-0\n\n[Event \"B\"]\n\n1. d4 Xd5\n";
+        let games = GameReader::new(pgn.as_bytes()).collect::<Vec<_>>();
+        let error = games.last().unwrap().as_ref().unwrap_err();
+
+        assert_eq!(
+            error.location(),
+            Some(Location {
+                offset: 46,
+                line: 7,
+                column: 7,
+            })
+        );
+        assert_eq!(error.token(), Some("Xd5"));
+    }
+
+    #[test]
+    fn test_lenient() {
+        let pgn = "[Event \"A\"]\n\n1. e4 {Unclosed 1-0\n\n[Event \"B\"]\n[White \"X\"]\n\n\
+                   1. d4 d5 0-1\n\n[Event \"C\"]\n\n(1. c4) 1-0\n\n[Event \"D\"]\n\n1. Nf3 *\n";
+
+        // Without lenient mode the unclosed comment swallows the other games
+        assert_eq!(GameReader::new(pgn.as_bytes()).count(), 1);
+
+        let mut reader = GameReader::new(pgn.as_bytes()).lenient();
+        let games = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[1].tags["White"], "X");
+        assert_eq!(games[1].moves.len(), 2);
+        assert_eq!(games[2].tags["Event"], "D");
+
+        assert_eq!(reader.errors().len(), 1);
+        assert_eq!(
+            reader.errors()[0].location().map(|location| location.line),
+            Some(12)
+        );
+    }
+
+    #[test]
+    fn test_comments() {
+        // Braces in a rest-of-line comment don't open a comment, and brace
+        // comments end at the first closing brace
+        let pgn = "[Event \"A\"]\n\n1. e4 ; see {x\ne5 1-0\n\n[Event \"B\"]\n\n\
+                   1. d4 {a {b} d5 0-1\n\n[Event \"C\"]\n\n1. c4 1-0\n";
+        let games = GameReader::new(pgn.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 3);
+        assert_eq!(games[0].moves.len(), 2);
+        assert_eq!(games[1].tags["Event"], "B");
+        assert_eq!(games[1].moves.len(), 2);
+        assert_eq!(games[2].tags["Event"], "C");
+    }
+
+    #[test]
+    fn test_escape_lines() {
+        let pgn = "% Exported\n[Event \"A\"]\n% [Event \"X\"]\n\n1. e4\n%1. d4\n e5 1-0\n";
+        let games = GameReader::new(pgn.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].tags.len(), 1);
+        assert_eq!(games[0].moves.len(), 2);
+    }
+
+    #[test]
+    fn test_latin1() {
+        let pgn = b"[White \"Sj\xf6berg\"]\n\n1. e4 1-0\n";
+        let games = GameReader::new(&pgn[..]).collect::<Vec<_>>();
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].as_ref().unwrap().tags["White"], "Sj\u{fffd}berg");
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(GameReader::new("\n\n".as_bytes()).count(), 0);
+    }
+}
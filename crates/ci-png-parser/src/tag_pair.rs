@@ -0,0 +1,150 @@
+use crate::{error::Location, PgnError};
+
+/// A tag pair such as `[Event "F/S Return Match"]`, with its value unescaped.
+#[derive(Debug, PartialEq)]
+pub(crate) struct TagPair {
+    pub name: String,
+    pub value: String,
+    /// Byte offset of the value within the input
+    pub offset: usize,
+}
+
+/// Reads the tag pairs of `line`, which starts at byte `offset` of `data`.
+///
+/// A tag pair is a name made of letters, digits and underscores followed by
+/// a quoted value, in which `\"` and `\\` stand for a quote and a backslash,
+/// all within brackets. Any number of them may share a line.
+pub(crate) fn parse_line(data: &str, offset: usize, line: &str) -> Result<Vec<TagPair>, PgnError> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim_start();
+
+    while !rest.is_empty() {
+        let start = line.len() - rest.len();
+        let invalid_tag = || {
+            let token = &line[start..];
+            let end = token.find(']').map_or(token.len(), |index| index + 1);
+
+            PgnError::InvalidTag {
+                location: Location::new(data, offset + start),
+                token: token[..end].trim_end().to_string(),
+            }
+        };
+
+        let body = rest.strip_prefix('[').ok_or_else(invalid_tag)?.trim_start();
+        let name_length = body
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(body.len());
+        let (name, body) = body.split_at(name_length);
+        if name.is_empty() {
+            return Err(invalid_tag());
+        }
+
+        let body = body
+            .trim_start()
+            .strip_prefix('"')
+            .ok_or_else(invalid_tag)?;
+        let value_offset = offset + line.len() - body.len();
+        let mut value = String::new();
+        let mut chars = body.char_indices();
+        let end = loop {
+            match chars.next().ok_or_else(invalid_tag)? {
+                (index, '"') => break index,
+                (_, '\\') => match chars.next().ok_or_else(invalid_tag)? {
+                    (_, escaped @ ('"' | '\\')) => value.push(escaped),
+                    // Other backslashes are kept as they are
+                    (_, c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                },
+                (_, c) => value.push(c),
+            }
+        };
+
+        rest = body[end + 1..]
+            .trim_start()
+            .strip_prefix(']')
+            .ok_or_else(invalid_tag)?
+            .trim_start();
+
+        pairs.push(TagPair {
+            name: name.to_string(),
+            value,
+            offset: value_offset,
+        });
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_line, TagPair};
+    use crate::PgnError;
+
+    fn pairs(line: &str) -> Vec<(String, String)> {
+        parse_line(line, 0, line)
+            .unwrap()
+            .into_iter()
+            .map(|pair| (pair.name, pair.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("[Event \"WCh 2021\"]", 0, "[Event \"WCh 2021\"]").unwrap(),
+            vec![TagPair {
+                name: String::from("Event"),
+                value: String::from("WCh 2021"),
+                offset: 8,
+            }]
+        );
+        assert_eq!(
+            pairs("  [ Round  \"4\" ] [White_Elo \"2855\"]\r\n"),
+            vec![
+                (String::from("Round"), String::from("4")),
+                (String::from("White_Elo"), String::from("2855")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(
+            pairs(r#"[Annotator "The \"Best\" [C:\\PGN]"]"#),
+            vec![(
+                String::from("Annotator"),
+                String::from(r#"The "Best" [C:\PGN]"#)
+            )]
+        );
+        assert_eq!(
+            pairs(r#"[Site "a\b"]"#),
+            vec![(String::from("Site"), String::from(r"a\b"))]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        for line in [
+            "[Event]",
+            "[\"A\"]",
+            "[Event \"A\"",
+            "[Event \"A]",
+            "[Event A]",
+            "[Event \"A\"] 1. e4",
+            "[Event-Name \"A\"]",
+        ] {
+            assert!(
+                matches!(parse_line(line, 0, line), Err(PgnError::InvalidTag { .. })),
+                "{}",
+                line
+            );
+        }
+
+        let data = "[Event \"A\"]\n[Site \"B\"] [Date 1992]\n";
+        let error = parse_line(data, 12, &data[12..]).unwrap_err();
+        assert_eq!(error.token(), Some("[Date 1992]"));
+        assert_eq!(error.location().map(|location| location.column), Some(12));
+    }
+}
@@ -0,0 +1,396 @@
+use std::io::{self, Write};
+
+use ci_core::{
+    board::Board,
+    game::{Game, MoveNode, Player},
+};
+
+use crate::{clock, png_move::PNGMove};
+
+/// Tags every game in export format carries, in this order, with the value
+/// written when a game lacks them
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+/// Movetext lines are kept within 80 columns
+const LINE_LENGTH: usize = 79;
+
+/// Writes a game in PGN export format.
+///
+/// The Seven Tag Roster comes first, followed by the other tags sorted by
+/// name. Moves are written in normalized SAN as far as the game can be
+/// replayed, and as they were read after that.
+///
+/// # Examples
+/// ```
+/// let game = ci_png_parser::parse("[White \"Carlsen,M\"]\n\n1. e4 e5 2. Nf3 1-0").unwrap();
+/// let pgn = ci_png_parser::write(&game);
+///
+/// assert!(pgn.starts_with("[Event \"?\"]\n"));
+/// assert!(pgn.ends_with("\n\n1. e4 e5 2. Nf3 1-0\n\n"));
+/// ```
+pub fn write(game: &Game) -> String {
+    let mut pgn = String::new();
+
+    for (name, value) in tags(game) {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, escape(&value)));
+    }
+    pgn.push('\n');
+
+    for line in wrap(&movetext(game)) {
+        pgn.push_str(&line);
+        pgn.push('\n');
+    }
+    pgn.push('\n');
+
+    pgn
+}
+
+/// Writes a game in PGN export format to `writer`, see [`write`].
+pub fn write_to(mut writer: impl Write, game: &Game) -> io::Result<()> {
+    writer.write_all(write(game).as_bytes())
+}
+
+/// The tags to write, the `Result` tag always agreeing with the game
+/// termination marker.
+fn tags(game: &Game) -> Vec<(&str, String)> {
+    let mut tags = SEVEN_TAG_ROSTER
+        .iter()
+        .map(|(name, default)| {
+            let value = match *name {
+                "Result" => game.result.to_string(),
+                _ => game.tags.get(name).unwrap_or(default).to_string(),
+            };
+
+            (*name, value)
+        })
+        .collect::<Vec<_>>();
+
+    let mut other = game
+        .tags
+        .iter()
+        .filter(|(name, _)| !SEVEN_TAG_ROSTER.iter().any(|(roster, _)| roster == name))
+        .map(|(name, value)| (name, value.to_string()))
+        .collect::<Vec<_>>();
+    other.sort();
+    tags.extend(other);
+
+    tags
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The tokens of the movetext, a move number is kept together with the move
+/// that follows it.
+fn movetext(game: &Game) -> Vec<String> {
+    let board = game.start_position().ok();
+    let number = board.as_ref().map_or(1, |board| board.fullmove_number);
+    let mut tokens = Vec::with_capacity(game.moves.len() + 1);
+
+    write_line(&mut tokens, &game.comments, &game.moves, board, number);
+
+    tokens.push(game.result.to_string());
+    tokens
+}
+
+/// Writes the tokens of a line of moves starting from `board`, where the
+/// first move is part of move `number`. Moves are written as they were read
+/// once the line cannot be replayed.
+fn write_line(
+    tokens: &mut Vec<String>,
+    comments: &[String],
+    moves: &[MoveNode],
+    mut board: Option<Board>,
+    mut number: u32,
+) {
+    write_comments(tokens, comments);
+
+    // Black's moves are numbered at the start of a line and after anything
+    // that interrupts it
+    let mut needs_number = true;
+
+    for node in moves {
+        let chess_move = &node.chess_move;
+        let before = board.clone();
+
+        let resolved = board
+            .as_ref()
+            .and_then(|board| board.resolve(chess_move).ok());
+        let san = match (&mut board, resolved) {
+            (Some(board), Some(resolved)) => {
+                let san = board.san(&resolved);
+                board.play(resolved);
+                san
+            }
+            _ => {
+                board = None;
+                PNGMove::to_notation(chess_move)
+            }
+        };
+
+        match chess_move.turn {
+            Player::White => tokens.push(format!("{}. {}", number, san)),
+            Player::Black if needs_number => tokens.push(format!("{}... {}", number, san)),
+            Player::Black => tokens.push(san),
+        }
+
+        tokens.extend(node.nags.iter().map(|nag| format!("${}", nag)));
+
+        let clock = clock::comment(node.clock, node.elapsed);
+        write_comments(tokens, clock.as_slice());
+        write_comments(tokens, &node.comments);
+
+        for variation in &node.variations {
+            let start = tokens.len();
+            write_line(
+                tokens,
+                &variation.comments,
+                &variation.moves,
+                before.clone(),
+                number,
+            );
+
+            if let Some(first) = tokens.get_mut(start) {
+                first.insert(0, '(');
+            }
+            match tokens.last_mut() {
+                // A rest-of-line comment would take the parenthesis in
+                Some(last) if last.ends_with('\n') => tokens.push(String::from(")")),
+                Some(last) => last.push(')'),
+                None => {}
+            }
+        }
+
+        needs_number = clock.is_some() || !node.comments.is_empty() || !node.variations.is_empty();
+        if chess_move.turn == Player::Black {
+            number += 1;
+        }
+    }
+}
+
+/// Brace comments, split into words so they can be wrapped. Empty comments
+/// are left out. A comment holding a `}` cannot be put in braces, it is
+/// written as a rest-of-line comment instead, a single token ending in a
+/// line break.
+fn write_comments(tokens: &mut Vec<String>, comments: &[String]) {
+    for comment in comments {
+        let mut words = comment
+            .split_whitespace()
+            .map(String::from)
+            .collect::<Vec<_>>();
+
+        if words.is_empty() {
+            continue;
+        }
+        if comment.contains('}') {
+            tokens.push(format!(";{}\n", words.join(" ")));
+            continue;
+        }
+
+        words[0].insert(0, '{');
+        if let Some(last) = words.last_mut() {
+            last.push('}');
+        }
+        tokens.append(&mut words);
+    }
+}
+
+/// Joins the tokens into lines of at most [`LINE_LENGTH`] characters. A
+/// token ending in a line break ends its line.
+fn wrap(tokens: &[String]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for token in tokens {
+        let (token, ends_line) = match token.strip_suffix('\n') {
+            Some(token) => (token, true),
+            None => (token.as_str(), false),
+        };
+
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_LENGTH {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(token);
+
+        if ends_line {
+            lines.push(std::mem::take(&mut line));
+        }
+    }
+    lines.push(line);
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, write};
+    use crate::parse;
+    use ci_core::tags::Tags;
+
+    #[test]
+    fn test_write() {
+        let game = parse(
+            r#"[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[White "Fischer, Robert J."]
+[ECO "C95"]
+[Annotator "Someone"]
+[Event "F/S Return Match"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3
+O-O 9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4 15.
+Nb1 h6 16. Bh4 c5 17. dxe5 Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6 1/2-1/2
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            write(&game),
+            r#"[Event "F/S Return Match"]
+[Site "?"]
+[Date "????.??.??"]
+[Round "?"]
+[White "Fischer, Robert J."]
+[Black "Spassky, Boris V."]
+[Result "1/2-1/2"]
+[Annotator "Someone"]
+[ECO "C95"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3
+O-O 9. h3 Nb8 10. d4 Nbd7 11. c4 c6 12. cxb5 axb5 13. Nc3 Bb7 14. Bg5 b4
+15. Nb1 h6 16. Bh4 c5 17. dxe5 Nxe4 18. Bxe7 Qxe7 19. exd6 Qf6 20. Nbd2 Nxd6
+1/2-1/2
+
+"#
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(r#"The "Immortal" game"#), r#"The \"Immortal\" game"#);
+        assert_eq!(escape(r"C:\games"), r"C:\\games");
+
+        let game = parse(
+            r#"[Event "The \"Immortal\" game"]
+
+1. e4 *"#,
+        )
+        .unwrap();
+        let reparsed = parse(&write(&game)).unwrap();
+        assert_eq!(reparsed.tags["Event"], r#"The "Immortal" game"#);
+    }
+
+    #[test]
+    fn test_write_clock() {
+        let game = parse(
+            "[TimeControl \"300+3\"]\n\n1. e4 {[%clk 0:04:58]} e5 {Solid [%clk 0:04:57.2345] [%emt 0:00:03]} *",
+        )
+        .unwrap();
+        let written = write(&game);
+
+        assert!(written.contains("[TimeControl \"300+3\"]\n"));
+        assert!(written.ends_with(
+            "\n\n1. e4 {[%clk 0:04:58]} 1... e5 {[%clk 0:04:57.2345] [%emt 0:00:03]} {Solid} *\n\n"
+        ));
+        assert_eq!(parse(&written).unwrap().moves, game.moves);
+    }
+
+    #[test]
+    fn test_write_normalizes() {
+        let game = parse("1. e4 e5 2. Ng1f3 Nb8c6 3. Bf1b5 a6 4. Bb5xc6 *").unwrap();
+
+        assert!(write(&game).ends_with("\n\n1. e4 e5 2. Nf3 Nc6 3. Bb5 a6 4. Bxc6 *\n\n"));
+    }
+
+    #[test]
+    fn test_write_annotations() {
+        let game = parse(
+            "{Opening} 1. e4 e5!? (1... c5 2. Nf3 (2. c3 $6) 2... d6) (1... e6 {French}) 2. Nf3 Nc6 {Now} 3. Bb5 1-0",
+        )
+        .unwrap();
+        let written = write(&game);
+
+        assert!(written.ends_with(
+            "\n\n{Opening} 1. e4 e5 $5 (1... c5 2. Nf3 (2. c3 $6) 2... d6) (1... e6 {French})\n2. Nf3 Nc6 {Now} 3. Bb5 1-0\n\n"
+        ));
+
+        let reparsed = parse(&written).unwrap();
+        assert_eq!(reparsed.comments, game.comments);
+        assert_eq!(reparsed.moves, game.moves);
+
+        // Comments with a closing brace are written to the end of the line
+        for (pgn, expected) in [
+            ("1. e4 ; a } b\ne5 *", "\n\n1. e4 ;a } b\n1... e5 *\n\n"),
+            (
+                "1. e4 (1. d4 ; x } y\n) e5 *",
+                "\n\n1. e4 (1. d4 ;x } y\n) 1... e5 *\n\n",
+            ),
+            ("; a } b\n1. e4 *", "\n\n;a } b\n1. e4 *\n\n"),
+        ] {
+            let game = parse(pgn).unwrap();
+            let written = write(&game);
+            assert!(written.ends_with(expected), "{:?}", written);
+
+            let reparsed = parse(&written).unwrap();
+            assert_eq!(reparsed.comments, game.comments);
+            assert_eq!(reparsed.moves, game.moves);
+        }
+
+        let mut game = parse("1. e4 e5 *").unwrap();
+        game.comments.push(String::from(" "));
+        assert!(write(&game).ends_with("\n\n1. e4 e5 *\n\n"));
+    }
+
+    #[test]
+    fn test_write_black_to_move() {
+        let game = parse(
+            "[FEN \"r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3\"]\n\n3. Bb5 a6 4. Ba4 *",
+        )
+        .unwrap();
+        assert!(write(&game).ends_with("\n\n3. Bb5 a6 4. Ba4 *\n\n"));
+
+        let game =
+            parse("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 12\"]\n\n12... Kd7 13. e4 *").unwrap();
+        assert!(write(&game).ends_with("\n\n12... Kd7 13. e4 *\n\n"));
+    }
+
+    #[test]
+    fn test_write_unplayable() {
+        // Moves that cannot be replayed are written as they were read
+        let game = parse("1. e4 e5 2. Ke3 Nc6 *").unwrap();
+
+        assert!(write(&game).ends_with("\n\n1. e4 e5 2. Ke3 Nc6 *\n\n"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pgn = std::fs::read_to_string("../../examples/game.pgn").unwrap();
+        let game = parse(&pgn).unwrap();
+        let written = write(&game);
+        let reparsed = parse(&written).unwrap();
+
+        assert_eq!(reparsed.moves, game.moves);
+        // Tags outside the Seven Tag Roster are written sorted by name
+        fn sorted(tags: &Tags) -> Vec<(&str, &str)> {
+            let mut tags = tags.iter().collect::<Vec<_>>();
+            tags.sort();
+            tags
+        }
+        assert_eq!(sorted(&reparsed.tags), sorted(&game.tags));
+        assert_eq!(reparsed.result, game.result);
+        assert_eq!(write(&reparsed), written);
+        assert!(written.lines().all(|line| line.len() <= 79));
+    }
+}
@@ -1,5 +1,9 @@
 use core::fmt;
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use ci_core::{
     board::{Board, Color},
@@ -7,9 +11,16 @@ use ci_core::{
     square::Square,
 };
 use eframe::egui;
-use egui::{ComboBox, Id, Rect};
+use egui::{ComboBox, Id, Key, Rect};
 use egui_extras::RetainedImage;
+use game_list::GameList;
 use lazy_static::lazy_static;
+use replay::{Cursor, Replay};
+
+mod game_list;
+mod header;
+mod move_list;
+mod replay;
 
 #[derive(Hash, PartialEq, Eq)]
 enum ChessPiece {
@@ -55,18 +66,25 @@ struct Image {
 
 impl Image {
     pub fn new(name: impl Into<String>, buffer: &[u8]) -> Self {
+        // Rendered well above the size of a square, so pieces stay sharp when
+        // scaled down to it
         Self {
             image: egui_extras::RetainedImage::from_svg_bytes_with_size(
                 name,
                 buffer,
-                egui_extras::image::FitTo::Original,
+                egui_extras::image::FitTo::Zoom(4.0),
             )
             .expect("Could not find image"),
         }
     }
 
+    /// Draws the image centered in `rect`, scaled to fit it.
     fn draw(&self, ui: &mut egui::Ui, context: &egui::Context, rect: Rect) {
-        egui::Image::new(self.image.texture_id(context), self.image.size_vec2()).paint_at(ui, rect);
+        let size = self.image.size_vec2();
+        let scale = (rect.width() / size.x).min(rect.height() / size.y);
+        let bounds = Rect::from_center_size(rect.center(), size * scale);
+
+        egui::Image::new(self.image.texture_id(context), bounds.size()).paint_at(ui, bounds);
     }
 }
 
@@ -75,21 +93,226 @@ fn main() -> Result<(), eframe::Error> {
         initial_window_size: Some(egui::vec2(600.0, 500.0)),
         ..Default::default()
     };
+
+    // A PGN file to open can be passed as the first argument
+    let path = std::env::args_os().nth(1).map(PathBuf::from);
+
     eframe::run_native(
         "svg example",
         options,
-        Box::new(|_cc| Box::new(MyApp::default())),
+        Box::new(|cc| {
+            let mut app = MyApp::default();
+            if let Some(path) = path {
+                app.open(&path, &cc.egui_ctx);
+            }
+
+            Box::new(app)
+        }),
     )
 }
 
 struct MyApp {
     chessboard: Chessboard,
+    replay: Option<Replay>,
+    /// Why the last file could not be opened
+    error: Option<String>,
+    /// Keeps the result of a game hidden until its last move is reached
+    spoiler_free: bool,
+    /// The games of the database opened last
+    games: Option<GameList>,
+    show_games: bool,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
         Self {
             chessboard: Chessboard::new(),
+            replay: None,
+            error: None,
+            spoiler_free: false,
+            games: None,
+            show_games: false,
+        }
+    }
+}
+
+impl MyApp {
+    /// Opens a PGN database. Its first game is shown once it has been read
+    /// and, when there are more, the list of its games.
+    fn open(&mut self, path: &Path, ctx: &egui::Context) {
+        self.games = Some(GameList::open(path, ctx));
+        self.show_games = false;
+        self.error = None;
+    }
+
+    /// Takes in the games of the database read since the last frame.
+    fn receive(&mut self) {
+        let Some(games) = &mut self.games else {
+            return;
+        };
+
+        let before = games.len();
+        if let Err(error) = games.receive() {
+            self.error = Some(error);
+        }
+
+        let after = games.len();
+        if after == 0 && !games.is_loading() {
+            self.games = None;
+        }
+        if before == 0 && after > 0 {
+            self.select(0);
+        }
+        if before <= 1 && after > 1 {
+            self.show_games = true;
+        }
+    }
+
+    /// Shows game `index` of the database.
+    fn select(&mut self, index: usize) {
+        let Some(games) = &self.games else {
+            return;
+        };
+
+        match games.game(index) {
+            Ok(game) => {
+                let replay = Replay::new(game);
+                self.chessboard.state = replay.board().clone();
+                self.replay = Some(replay);
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    /// The window listing the games of the database.
+    fn game_list(&mut self, ctx: &egui::Context) {
+        let Some(games) = &mut self.games else {
+            return;
+        };
+
+        let mut clicked = None;
+        egui::Window::new("Games")
+            .open(&mut self.show_games)
+            .default_width(600.0)
+            .show(ctx, |ui| clicked = games.show(ui, self.spoiler_free));
+
+        if let Some(index) = clicked {
+            self.select(index);
+        }
+    }
+
+    /// Moves through the game with `navigate`, showing the position it
+    /// leads to.
+    fn navigate(&mut self, navigate: impl FnOnce(&mut Replay)) {
+        if let Some(replay) = &mut self.replay {
+            navigate(replay);
+            self.chessboard.state = replay.board().clone();
+        }
+    }
+
+    fn menu(&mut self, ui: &mut egui::Ui) {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                if ui.button("Open…").clicked() {
+                    ui.close_menu();
+
+                    let path = rfd::FileDialog::new()
+                        .add_filter("PGN", &["pgn"])
+                        .pick_file();
+                    if let Some(path) = path {
+                        self.open(&path, ui.ctx());
+                    }
+                }
+            });
+            ui.menu_button("View", |ui| {
+                ui.add_enabled(
+                    self.games.is_some(),
+                    egui::Checkbox::new(&mut self.show_games, "Games"),
+                );
+                ui.checkbox(&mut self.spoiler_free, "Hide result until the last move");
+            });
+        });
+    }
+
+    fn navigation(&mut self, ui: &mut egui::Ui) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+        let (ply, plies) = (replay.ply(), replay.plies());
+        let at_start = *replay.cursor() == Cursor::default();
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!at_start, egui::Button::new("⏮")).clicked() {
+                self.navigate(Replay::first);
+            }
+            if ui.add_enabled(!at_start, egui::Button::new("⏴")).clicked() {
+                self.navigate(Replay::previous);
+            }
+            if ui
+                .add_enabled(ply < plies, egui::Button::new("⏵"))
+                .clicked()
+            {
+                self.navigate(Replay::next);
+            }
+            if ui
+                .add_enabled(ply < plies, egui::Button::new("⏭"))
+                .clicked()
+            {
+                self.navigate(Replay::last);
+            }
+        });
+        ui.label(format!("Move {} of {}", ply, plies));
+    }
+
+    /// The moves of the game, clicking one shows the position after it.
+    fn move_list(&mut self, ui: &mut egui::Ui) {
+        let Some(replay) = &self.replay else {
+            return;
+        };
+
+        let clicked = egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                move_list::show(ui, &replay.lines, &mut Vec::new(), replay.cursor())
+            })
+            .inner;
+
+        if let Some(cursor) = clicked {
+            self.navigate(|replay| {
+                replay.go_to(cursor);
+            });
+        }
+    }
+
+    /// The arrow keys step through the game, home and end jump to its start
+    /// and end.
+    fn keyboard(&mut self, ctx: &egui::Context) {
+        // The keys move the text cursor while a text field has focus
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let (first, previous, next, last) = ctx.input(|input| {
+            (
+                input.key_pressed(Key::Home),
+                input.key_pressed(Key::ArrowLeft),
+                input.key_pressed(Key::ArrowRight),
+                input.key_pressed(Key::End),
+            )
+        });
+
+        if first {
+            self.navigate(Replay::first);
+        }
+        if previous {
+            self.navigate(Replay::previous);
+        }
+        if next {
+            self.navigate(Replay::next);
+        }
+        if last {
+            self.navigate(Replay::last);
         }
     }
 }
@@ -217,42 +440,39 @@ impl Chessboard {
     }
 }
 
-static SQUARE_SIZE: f32 = 50.0;
+/// Part of a square taken up by the piece on it
+const PIECE_SCALE: f32 = 0.9;
 
 struct ChessboardSquare<'a> {
+    square: Square,
     piece: &'a Option<(Piece, Color)>,
-    coords: (i8, i8),
     bounds: Rect,
 }
 
 impl<'a> ChessboardSquare<'a> {
-    pub fn new(index: u8, piece: &'a Option<(Piece, Color)>) -> Self {
-        let (x, y) = Square(index).coordinate();
-
+    pub fn new(square: Square, piece: &'a Option<(Piece, Color)>, bounds: Rect) -> Self {
         Self {
+            square,
             piece,
-            coords: (x, y),
-            bounds: Rect {
-                min: egui::Pos2::new(x as f32 * SQUARE_SIZE, y as f32 * SQUARE_SIZE),
-                max: egui::Pos2::new(
-                    (x as f32 + 1.0) * SQUARE_SIZE,
-                    (y as f32 + 1.0) * SQUARE_SIZE,
-                ),
-            },
+            bounds,
         }
     }
 
+    /// Whether this is a dark square, a1 being one.
+    fn is_dark(&self) -> bool {
+        let (file, rank) = self.square.coordinate();
+        (file + rank) % 2 == 0
+    }
+
     pub fn draw(&self, ui: &mut egui::Ui) {
-        let (x, y) = self.coords;
-        let bg_color = if (x + y) % 2 == 0 {
-            egui::Color32::WHITE
-        } else {
+        let bg_color = if self.is_dark() {
             egui::Color32::BROWN
+        } else {
+            egui::Color32::WHITE
         };
 
-        let painter = ui.painter_at(self.bounds);
         ui.painter()
-            .rect_filled(painter.clip_rect(), egui::Rounding::default(), bg_color);
+            .rect_filled(self.bounds, egui::Rounding::none(), bg_color);
 
         // Optionally draw a chess piece if the square contains it
         if let Some((piece, color)) = self.piece {
@@ -267,68 +487,96 @@ impl<'a> ChessboardSquare<'a> {
                 .get(&cp)
                 .expect(format!("Could not find image for {}", piece_color).as_str());
 
-            // TODO: the chess pieces don't fit nicely in their squares
-            img.draw(ui, &ui.ctx().to_owned(), self.bounds);
+            let bounds =
+                Rect::from_center_size(self.bounds.center(), self.bounds.size() * PIECE_SCALE);
+            img.draw(ui, &ui.ctx().to_owned(), bounds);
+        }
+    }
+
+    /// Labels the square with its file and rank where they are needed, in
+    /// the color of the other squares so they stand out.
+    fn draw_coordinates(&self, ui: &mut egui::Ui, file: bool, rank: bool) {
+        let color = if self.is_dark() {
+            egui::Color32::WHITE
+        } else {
+            egui::Color32::BROWN
+        };
+        let font = egui::FontId::proportional(self.bounds.height() * 0.2);
+        let margin = self.bounds.height() * 0.05;
+
+        if file {
+            ui.painter().text(
+                self.bounds.right_bottom() - egui::vec2(margin, margin),
+                egui::Align2::RIGHT_BOTTOM,
+                self.square.file().to_str(),
+                font.clone(),
+                color,
+            );
+        }
+        if rank {
+            ui.painter().text(
+                self.bounds.left_top() + egui::vec2(margin, margin),
+                egui::Align2::LEFT_TOP,
+                self.square.rank().to_str(),
+                font,
+                color,
+            );
         }
     }
 }
 
 impl Chessboard {
+    /// Draws the board as large as fits in the space available, with the
+    /// side of `perspective` at the bottom.
     pub fn ui(&mut self, ui: &mut egui::Ui) {
-        ui.set_max_size(egui::Vec2::new(500.0, 500.0));
+        let size = ui.available_size().min_elem().max(0.0);
+        let (response, _) = ui.allocate_painter(egui::Vec2::splat(size), egui::Sense::hover());
+        let square_size = size / 8.0;
 
         for (index, piece) in self.state.squares.iter().enumerate() {
-            let idx = if self.perspective == Perspective::White {
-                63 - index
-            } else {
-                index
-            };
+            let square = Square(index as u8);
+            let (file, rank) = square.coordinate();
 
-            let square = ChessboardSquare::new(idx as u8, piece);
+            // Column and row on screen, counted from the top left
+            let (column, row) = match self.perspective {
+                Perspective::White => (file, 7 - rank),
+                Perspective::Black => (7 - file, rank),
+            };
+            let min = response.rect.min
+                + egui::vec2(column as f32 * square_size, row as f32 * square_size);
+            let bounds = Rect::from_min_size(min, egui::Vec2::splat(square_size));
 
+            let square = ChessboardSquare::new(square, piece, bounds);
             square.draw(ui);
-            self.drawn = true;
+            // Files are labelled along the bottom edge, ranks along the left
+            square.draw_coordinates(ui, row == 7, column == 0);
         }
-    }
-
-    pub fn move_piece(&mut self) {
-        let from = Square::new(ci_core::rank::Rank::Two, ci_core::file::File::A);
-        let to = Square::new(ci_core::rank::Rank::Three, ci_core::file::File::A);
-
-        let mut current_square: &Option<(Piece, Color)> = self
-            .state
-            .squares
-            .get(from.to_index())
-            .expect("Square should exist");
-
-        let mut to_square = self
-            .state
-            .squares
-            .get(to.to_index())
-            .expect("Square should exist");
 
-        let binding = current_square.to_owned();
-        to_square = &binding;
-        current_square = &None;
-
-        self.state.squares[from.to_index()] = *current_square;
-        self.state.squares[to.to_index()] = *to_square;
-    }
-
-    pub fn reset(&mut self) {
-        self.state = Board::default();
+        self.drawn = true;
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            self.chessboard.ui(ui);
+        self.receive();
+        self.keyboard(ctx);
+
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            self.menu(ui);
         });
+        self.game_list(ctx);
+
+        if let Some(replay) = &self.replay {
+            let hide_result = self.spoiler_free && !replay.is_at_end();
+
+            egui::TopBottomPanel::top("header").show(ctx, |ui| {
+                header::show(ui, &replay.game.tags, replay.game.result, hide_result);
+            });
+        }
 
         egui::SidePanel::new(egui::panel::Side::Right, Id::new("something"))
-            .max_width(200.0)
-            .resizable(false)
+            .min_width(200.0)
+            .resizable(true)
             .show_separator_line(true)
             .show(ctx, |ui| {
                 ui.label("Perspective");
@@ -348,20 +596,27 @@ impl eframe::App for MyApp {
                         );
                     });
 
-                if (ui.button("Play move")).clicked() {
-                    self.chessboard.move_piece();
-                }
+                ui.separator();
+                self.navigation(ui);
 
-                if (ui.button("Reset")).clicked() {
-                    self.chessboard.reset();
+                if let Some(error) = &self.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if let Some(error) = self
+                    .replay
+                    .as_ref()
+                    .and_then(|replay| replay.error.as_ref())
+                {
+                    ui.colored_label(egui::Color32::RED, error);
                 }
+
+                ui.separator();
+                self.move_list(ui);
             });
-    }
-}
 
-pub fn coordinates_to_rec_min_max(x: f32, y: f32, size: f32) -> egui::Rect {
-    Rect {
-        min: egui::Pos2::new(x * size, y * size),
-        max: egui::Pos2::new((x + 1.0) * size, (y + 1.0) * size),
+        // Shown last, so the board takes up the space the panels leave
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.chessboard.ui(ui);
+        });
     }
 }
@@ -0,0 +1,380 @@
+use std::{
+    cmp::Ordering,
+    fs,
+    io::{BufReader, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use ci_core::game::Game;
+use ci_png_parser::{GameHeader, GameReader};
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+
+/// Number of headers read before they are handed to the list
+const BATCH_SIZE: usize = 1000;
+
+/// The columns of the game list, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    White,
+    Black,
+    Result,
+    Date,
+    Event,
+    Eco,
+    Plies,
+}
+
+impl Field {
+    const ALL: [Field; 7] = [
+        Field::White,
+        Field::Black,
+        Field::Result,
+        Field::Date,
+        Field::Event,
+        Field::Eco,
+        Field::Plies,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Field::White => "White",
+            Field::Black => "Black",
+            Field::Result => "Result",
+            Field::Date => "Date",
+            Field::Event => "Event",
+            Field::Eco => "ECO",
+            Field::Plies => "Plies",
+        }
+    }
+
+    /// The tag holding the field, for fields that are tags.
+    fn tag(&self) -> Option<&'static str> {
+        match self {
+            Field::White => Some("White"),
+            Field::Black => Some("Black"),
+            Field::Result => Some("Result"),
+            Field::Date => Some("Date"),
+            Field::Event => Some("Event"),
+            Field::Eco => Some("ECO"),
+            Field::Plies => None,
+        }
+    }
+
+    /// The text shown for a game.
+    fn value(&self, header: &GameHeader) -> String {
+        match self.tag() {
+            Some(tag) => header.tags.get(tag).unwrap_or_default().to_string(),
+            None => header.plies.to_string(),
+        }
+    }
+
+    fn compare(&self, a: &GameHeader, b: &GameHeader) -> Ordering {
+        match self.tag() {
+            // Dates in `YYYY.MM.DD` format sort as text
+            Some(tag) => a.tags.get(tag).cmp(&b.tags.get(tag)),
+            None => a.plies.cmp(&b.plies),
+        }
+    }
+
+    /// Whether the field of a game contains `filter`, which is lowercase.
+    fn matches(&self, header: &GameHeader, filter: &str) -> bool {
+        match self.tag() {
+            Some(tag) => header
+                .tags
+                .get(tag)
+                .map_or(false, |value| value.to_lowercase().contains(filter)),
+            None => header.plies.to_string().contains(filter),
+        }
+    }
+}
+
+/// What the thread reading a database sends to its list.
+enum Message {
+    Headers(Vec<GameHeader>),
+    /// The whole database was read, some games could not be
+    Done {
+        skipped: usize,
+    },
+    Failed(String),
+}
+
+/// The games of a PGN database, listed in a table that can be sorted and
+/// filtered.
+///
+/// Only the tag pairs of the games are kept, a game is read in full when it
+/// is opened. The database is read on a thread of its own, the games are
+/// listed as they come in.
+pub struct GameList {
+    path: PathBuf,
+    headers: Vec<GameHeader>,
+    /// Receives the headers until the whole database was read
+    loading: Option<Receiver<Message>>,
+    /// Games that could not be read and were skipped
+    pub skipped: usize,
+    sort: Field,
+    ascending: bool,
+    filter: String,
+    /// Whether the result of the games is left out
+    hide_result: bool,
+    /// Indices of the games shown, in the order they are shown
+    shown: Vec<usize>,
+    selected: Option<usize>,
+}
+
+impl GameList {
+    /// Starts reading the games of the PGN file at `path`, skipping the ones
+    /// that cannot be parsed. `ctx` is repainted as they come in.
+    pub fn open(path: &Path, ctx: &egui::Context) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (reading, ctx) = (path.to_path_buf(), ctx.clone());
+
+        thread::spawn(move || {
+            let message = match read_headers(&reading, &sender, &ctx) {
+                Ok(skipped) => Message::Done { skipped },
+                Err(error) => Message::Failed(error),
+            };
+
+            // The list is gone when another database was opened
+            let _ = sender.send(message);
+            ctx.request_repaint();
+        });
+
+        Self {
+            path: path.to_path_buf(),
+            headers: Vec::new(),
+            loading: Some(receiver),
+            skipped: 0,
+            sort: Field::Date,
+            ascending: true,
+            filter: String::new(),
+            hide_result: false,
+            shown: Vec::new(),
+            selected: Some(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// Takes in the games read since the last call. Fails when the database
+    /// could not be read, or holds no games.
+    pub fn receive(&mut self) -> Result<(), String> {
+        let Some(receiver) = &self.loading else {
+            return Ok(());
+        };
+
+        let mut received = false;
+        let mut result = Ok(());
+        while let Ok(message) = receiver.try_recv() {
+            match message {
+                Message::Headers(headers) => {
+                    self.headers.extend(headers);
+                    received = true;
+                }
+                Message::Done { skipped } => {
+                    self.skipped = skipped;
+                    self.loading = None;
+                    if self.headers.is_empty() {
+                        result = Err(format!("{} holds no games", self.path.display()));
+                    }
+                    break;
+                }
+                Message::Failed(error) => {
+                    self.loading = None;
+                    result = Err(error);
+                    break;
+                }
+            }
+        }
+
+        if received {
+            self.refresh();
+        }
+
+        result
+    }
+
+    /// Reads game `index` of the database in full.
+    pub fn game(&self, index: usize) -> Result<Game, String> {
+        let header = self
+            .headers
+            .get(index)
+            .ok_or_else(|| format!("There is no game {}", index + 1))?;
+        let start = header.location;
+
+        let mut file = fs::File::open(&self.path).map_err(|error| error.to_string())?;
+        file.seek(SeekFrom::Start(start.offset as u64))
+            .map_err(|error| error.to_string())?;
+
+        GameReader::new(BufReader::new(file))
+            .starting_at(start)
+            .next()
+            .ok_or_else(|| format!("Game {} could not be found", index + 1))?
+            .map_err(|error| error.to_string())
+    }
+
+    /// Applies the filter and sort order to the games shown.
+    fn refresh(&mut self) {
+        let filter = self.filter.to_lowercase();
+        let fields = self.fields();
+        let headers = &self.headers;
+
+        self.shown = (0..headers.len())
+            .filter(|index| {
+                filter.is_empty()
+                    || fields
+                        .iter()
+                        .any(|field| field.matches(&headers[*index], &filter))
+            })
+            .collect();
+
+        let (sort, ascending) = (self.sort, self.ascending);
+        self.shown.sort_by(|a, b| {
+            let ordering = sort.compare(&headers[*a], &headers[*b]);
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+
+    /// The fields shown as columns, the result being left out when it is
+    /// hidden.
+    fn fields(&self) -> Vec<Field> {
+        Field::ALL
+            .into_iter()
+            .filter(|field| !self.hide_result || *field != Field::Result)
+            .collect()
+    }
+
+    /// Shows the filter and the table of games, without their results when
+    /// `hide_result` is set. Returns the index of the game that was clicked,
+    /// if any.
+    pub fn show(&mut self, ui: &mut egui::Ui, hide_result: bool) -> Option<usize> {
+        if hide_result != self.hide_result {
+            self.hide_result = hide_result;
+            // Sorting by result would give it away as well
+            if hide_result && self.sort == Field::Result {
+                self.sort = Field::Date;
+                self.ascending = true;
+            }
+            self.refresh();
+        }
+
+        let mut clicked = None;
+
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            if ui.text_edit_singleline(&mut self.filter).changed() {
+                self.refresh();
+            }
+            ui.label(format!(
+                "{} of {} games",
+                self.shown.len(),
+                self.headers.len()
+            ));
+            if self.is_loading() {
+                ui.spinner();
+            }
+            if self.skipped > 0 {
+                ui.weak(format!("({} could not be read)", self.skipped));
+            }
+        });
+
+        let fields = self.fields();
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let mut sort = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .columns(Column::auto().resizable(true), fields.len() - 1)
+            .column(Column::remainder())
+            .header(row_height, |mut header| {
+                for field in &fields {
+                    header.col(|ui| {
+                        let title = match (self.sort == *field, self.ascending) {
+                            (true, true) => format!("{} ⏶", field.title()),
+                            (true, false) => format!("{} ⏷", field.title()),
+                            (false, _) => field.title().to_string(),
+                        };
+
+                        if ui.selectable_label(self.sort == *field, title).clicked() {
+                            sort = Some(*field);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, self.shown.len(), |row_index, mut row| {
+                    let index = self.shown[row_index];
+                    let header = &self.headers[index];
+
+                    for field in &fields {
+                        row.col(|ui| {
+                            let selected = self.selected == Some(index);
+                            if ui.selectable_label(selected, field.value(header)).clicked() {
+                                clicked = Some(index);
+                            }
+                        });
+                    }
+                });
+            });
+
+        // Clicking the column the games are sorted by reverses the order
+        if let Some(field) = sort {
+            self.ascending = self.sort != field || !self.ascending;
+            self.sort = field;
+            self.refresh();
+        }
+
+        if clicked.is_some() {
+            self.selected = clicked;
+        }
+
+        clicked
+    }
+}
+
+/// Reads the headers of the games of the PGN file at `path` and sends them
+/// in batches, repainting `ctx` after each one. Returns the number of games
+/// that could not be read.
+fn read_headers(
+    path: &Path,
+    sender: &Sender<Message>,
+    ctx: &egui::Context,
+) -> Result<usize, String> {
+    let file = fs::File::open(path).map_err(|error| error.to_string())?;
+    let mut reader = GameReader::new(BufReader::new(file)).lenient();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    loop {
+        let header = reader
+            .next_header()
+            .transpose()
+            .map_err(|error| error.to_string())?;
+        let done = header.is_none();
+        batch.extend(header);
+
+        if batch.len() == BATCH_SIZE || (done && !batch.is_empty()) {
+            let headers = std::mem::replace(&mut batch, Vec::with_capacity(BATCH_SIZE));
+            if sender.send(Message::Headers(headers)).is_err() {
+                // Nobody is listening anymore
+                return Ok(reader.errors().len());
+            }
+            ctx.request_repaint();
+        }
+
+        if done {
+            return Ok(reader.errors().len());
+        }
+    }
+}
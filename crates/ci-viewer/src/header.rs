@@ -0,0 +1,96 @@
+use ci_core::{
+    game::GameResult,
+    tags::Tags,
+    time_control::{Period, TimeControl},
+};
+use eframe::egui;
+
+/// Shows the players, event and other details of a game, and its result
+/// unless `hide_result` is set.
+pub fn show(ui: &mut egui::Ui, tags: &Tags, result: GameResult, hide_result: bool) {
+    let player = |name: Option<&str>, elo: Option<&str>| {
+        let name = name.unwrap_or("?");
+        match elo.filter(|elo| !elo.is_empty() && *elo != "?") {
+            Some(elo) => format!("{} ({})", name, elo),
+            None => name.to_string(),
+        }
+    };
+
+    ui.heading(format!(
+        "{} – {}",
+        player(tags.white(), tags.get("WhiteElo")),
+        player(tags.black(), tags.get("BlackElo"))
+    ));
+
+    egui::Grid::new("header").num_columns(2).show(ui, |ui| {
+        let mut row = |name: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|value| !value.is_empty() && value != "?") {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            }
+        };
+
+        row("Event", tags.event().map(String::from));
+        row("Site", tags.site().map(String::from));
+        row("Date", tags.get("Date").map(String::from));
+        row("Round", tags.round().map(String::from));
+        row("Opening", opening(tags));
+        row("Time control", tags.time_control().as_ref().map(describe));
+        row(
+            "Result",
+            Some(if hide_result {
+                String::from("Hidden until the last move")
+            } else {
+                result.to_string()
+            }),
+        );
+    });
+}
+
+/// The ECO code and name of the opening, as in `C42 Petrov, classical
+/// attack`.
+fn opening(tags: &Tags) -> Option<String> {
+    let name = match (tags.get("Opening"), tags.get("Variation")) {
+        (Some(opening), Some(variation)) => Some(format!("{}, {}", opening, variation)),
+        (opening, _) => opening.map(String::from),
+    };
+
+    match (tags.get("ECO"), name) {
+        (Some(eco), Some(name)) => Some(format!("{} {}", eco, name)),
+        (eco, name) => eco.map(String::from).or(name),
+    }
+}
+
+/// Describes a time control, as in `5 min + 3 s`.
+fn describe(time_control: &TimeControl) -> String {
+    let time = |seconds: u32, increment: u32| {
+        let mut time = match seconds % 60 {
+            0 => format!("{} min", seconds / 60),
+            _ => format!("{} s", seconds),
+        };
+        if increment > 0 {
+            time.push_str(&format!(" + {} s", increment));
+        }
+
+        time
+    };
+
+    match time_control {
+        TimeControl::Unknown => String::from("?"),
+        TimeControl::Unlimited => String::from("None"),
+        TimeControl::Periods(periods) => periods
+            .iter()
+            .map(|period| match *period {
+                Period::Moves {
+                    moves,
+                    seconds,
+                    increment,
+                } => format!("{} moves in {}", moves, time(seconds, increment)),
+                Period::SuddenDeath { seconds, increment } => time(seconds, increment),
+                Period::Sandclock { seconds } => format!("Sandclock of {}", time(seconds, 0)),
+            })
+            .collect::<Vec<_>>()
+            .join(", then "),
+    }
+}
@@ -0,0 +1,168 @@
+use ci_core::{
+    board::Board,
+    game::{MoveNode, Player},
+};
+use ci_png_parser::png_move::PNGMove;
+use eframe::egui;
+
+use crate::replay::Cursor;
+
+/// A line of moves as shown in the move list.
+pub struct Line {
+    /// Comments before the first move
+    pub comments: Vec<String>,
+    pub moves: Vec<ListedMove>,
+}
+
+/// A move as shown in the move list.
+pub struct ListedMove {
+    pub number: u32,
+    pub turn: Player,
+    /// The move in SAN, followed by its annotation glyphs
+    pub san: String,
+    pub comments: Vec<String>,
+    pub variations: Vec<Line>,
+}
+
+/// Writes out a line played from `board` in SAN. Moves are written as they
+/// were read once the line cannot be replayed any further.
+pub fn list(comments: &[String], moves: &[MoveNode], mut board: Option<Board>) -> Line {
+    let mut number = board.as_ref().map_or(1, |board| board.fullmove_number);
+    let mut listed = Vec::new();
+
+    for node in moves {
+        let chess_move = &node.chess_move;
+        let before = board.clone();
+
+        let resolved = board
+            .as_ref()
+            .and_then(|board| board.resolve(chess_move).ok());
+        let mut san = match (&mut board, resolved) {
+            (Some(board), Some(resolved)) => {
+                let san = board.san(&resolved);
+                board.play(resolved);
+                san
+            }
+            _ => {
+                board = None;
+                PNGMove::to_notation(chess_move)
+            }
+        };
+        san.extend(node.nags.iter().map(|nag| glyph(*nag)));
+
+        listed.push(ListedMove {
+            number,
+            turn: chess_move.turn,
+            san,
+            comments: node.comments.clone(),
+            variations: node
+                .variations
+                .iter()
+                .map(|variation| list(&variation.comments, &variation.moves, before.clone()))
+                .collect(),
+        });
+
+        if chess_move.turn == Player::Black {
+            number += 1;
+        }
+    }
+
+    Line {
+        comments: comments.to_vec(),
+        moves: listed,
+    }
+}
+
+/// The move suffix for a Numeric Annotation Glyph, or the glyph itself when
+/// it has none.
+fn glyph(nag: u8) -> String {
+    match nag {
+        1 => String::from("!"),
+        2 => String::from("?"),
+        3 => String::from("!!"),
+        4 => String::from("??"),
+        5 => String::from("!?"),
+        6 => String::from("?!"),
+        _ => format!(" ${}", nag),
+    }
+}
+
+/// Shows `line`, reached by taking `branches`, with the move at `current`
+/// highlighted. Returns where to go when a move was clicked.
+pub fn show(
+    ui: &mut egui::Ui,
+    line: &Line,
+    branches: &mut Vec<(usize, usize)>,
+    current: &Cursor,
+) -> Option<Cursor> {
+    let mut clicked = None;
+    let mut start = 0;
+
+    loop {
+        // The line is broken after each move with variations, which are
+        // shown indented below it
+        let end = line.moves[start..]
+            .iter()
+            .position(|listed| !listed.variations.is_empty())
+            .map_or(line.moves.len(), |index| start + index + 1);
+
+        ui.horizontal_wrapped(|ui| {
+            if start == 0 {
+                for comment in &line.comments {
+                    ui.weak(comment);
+                }
+            }
+
+            for index in start..end {
+                let listed = &line.moves[index];
+
+                // Black's moves are numbered where the line is interrupted
+                let interrupted = index == start || !line.moves[index - 1].comments.is_empty();
+                match listed.turn {
+                    Player::White => {
+                        ui.label(format!("{}.", listed.number));
+                    }
+                    Player::Black if interrupted => {
+                        ui.label(format!("{}...", listed.number));
+                    }
+                    Player::Black => {}
+                }
+
+                let cursor = Cursor {
+                    branches: branches.clone(),
+                    ply: index + 1,
+                };
+                if ui
+                    .selectable_label(cursor == *current, &listed.san)
+                    .clicked()
+                {
+                    clicked = Some(cursor);
+                }
+
+                for comment in &listed.comments {
+                    ui.weak(comment);
+                }
+            }
+        });
+
+        if let Some(index) = end.checked_sub(1).filter(|index| *index >= start) {
+            for (number, variation) in line.moves[index].variations.iter().enumerate() {
+                branches.push((index, number));
+
+                let id = egui::Id::new(&*branches);
+                ui.indent(id, |ui| {
+                    if let Some(cursor) = show(ui, variation, branches, current) {
+                        clicked = Some(cursor);
+                    }
+                });
+
+                branches.pop();
+            }
+        }
+
+        if end == line.moves.len() {
+            return clicked;
+        }
+        start = end;
+    }
+}
@@ -0,0 +1,178 @@
+use ci_core::{
+    board::Board,
+    game::{Game, MoveNode},
+};
+
+use crate::move_list::{self, Line};
+
+/// Where a position lies in the tree of moves of a game.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cursor {
+    /// The variations taken to reach the position, outermost first: the
+    /// index of the move each one is an alternative to within its line, and
+    /// the index of the variation
+    pub branches: Vec<(usize, usize)>,
+    /// Number of moves played in the innermost line
+    pub ply: usize,
+}
+
+/// A game loaded into the viewer, along with the position currently shown.
+pub struct Replay {
+    pub game: Game,
+    /// The moves of the game, as shown in the move list
+    pub lines: Line,
+    start: Board,
+    cursor: Cursor,
+    board: Board,
+    /// Why the game could not be replayed to its end
+    pub error: Option<String>,
+}
+
+impl Replay {
+    pub fn new(game: Game) -> Self {
+        let (start, mut error) = match game.start_position() {
+            Ok(board) => (board, None),
+            Err(fen) => (Board::default(), Some(fen.to_string())),
+        };
+
+        if error.is_none() {
+            error = game
+                .positions()
+                .find_map(Result::err)
+                .map(|replay| replay.to_string());
+        }
+
+        Self {
+            lines: move_list::list(&game.comments, &game.moves, Some(start.clone())),
+            game,
+            board: start.clone(),
+            start,
+            cursor: Cursor::default(),
+            error,
+        }
+    }
+
+    /// The position shown.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    /// Number of moves played in the line shown.
+    pub fn ply(&self) -> usize {
+        self.cursor.ply
+    }
+
+    /// Number of moves in the line shown.
+    pub fn plies(&self) -> usize {
+        self.line(&self.cursor.branches).map_or(0, <[_]>::len)
+    }
+
+    /// Whether the position shown is the last one of the game that can be
+    /// reached.
+    pub fn is_at_end(&self) -> bool {
+        let next = Cursor {
+            branches: Vec::new(),
+            ply: self.ply() + 1,
+        };
+
+        self.cursor.branches.is_empty()
+            && (self.ply() == self.plies() || self.position(&next).is_none())
+    }
+
+    /// The moves of the line reached by taking `branches`.
+    fn line(&self, branches: &[(usize, usize)]) -> Option<&[MoveNode]> {
+        branches
+            .iter()
+            .try_fold(&self.game.moves[..], |line, (index, variation)| {
+                line.get(*index)?
+                    .variations
+                    .get(*variation)
+                    .map(|variation| &variation.moves[..])
+            })
+    }
+
+    /// Replays the game up to `cursor`, `None` when a move on the way cannot
+    /// be played.
+    fn position(&self, cursor: &Cursor) -> Option<Board> {
+        let mut board = self.start.clone();
+        let mut line = &self.game.moves[..];
+        let mut play = |moves: &[MoveNode]| {
+            for node in moves {
+                let resolved = board.resolve(&node.chess_move).ok()?;
+                board.play(resolved);
+            }
+
+            Some(())
+        };
+
+        // A variation starts from the position before the move it replaces
+        for (index, variation) in &cursor.branches {
+            play(line.get(..*index)?)?;
+            line = &line[*index].variations.get(*variation)?.moves[..];
+        }
+        play(line.get(..cursor.ply)?)?;
+
+        Some(board)
+    }
+
+    /// Shows the position at `cursor`, unless it cannot be reached. Returns
+    /// whether it could.
+    pub fn go_to(&mut self, cursor: Cursor) -> bool {
+        match self.position(&cursor) {
+            Some(board) => {
+                self.board = board;
+                self.cursor = cursor;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns to the start of the game.
+    pub fn first(&mut self) {
+        self.go_to(Cursor::default());
+    }
+
+    /// Takes back a move, leaving a variation for the line it branched off
+    /// at its start.
+    pub fn previous(&mut self) {
+        let mut cursor = self.cursor.clone();
+
+        match cursor.ply {
+            0 => match cursor.branches.pop() {
+                Some((index, _)) => cursor.ply = index,
+                None => return,
+            },
+            ply => cursor.ply = ply - 1,
+        }
+
+        self.go_to(cursor);
+    }
+
+    pub fn next(&mut self) {
+        if self.ply() < self.plies() {
+            self.go_to(Cursor {
+                ply: self.ply() + 1,
+                ..self.cursor.clone()
+            });
+        }
+    }
+
+    /// Goes to the end of the line shown, or as far as it can be played.
+    pub fn last(&mut self) {
+        for ply in (self.ply()..=self.plies()).rev() {
+            let cursor = Cursor {
+                ply,
+                ..self.cursor.clone()
+            };
+
+            if self.go_to(cursor) {
+                return;
+            }
+        }
+    }
+}
@@ -1,10 +1,9 @@
-use lib::game::Game;
 use std::fs;
 
 fn main() {
     let contents =
         fs::read_to_string("examples/game.pgn").expect("Something went wrong reading the file");
-    let game = Game::from_string(&contents);
+    let game = ci_png_parser::parse(&contents).expect("Could not parse game");
 
     println!("{:?}", game);
 }
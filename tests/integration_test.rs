@@ -1,26 +1,102 @@
 #![cfg(test)]
 mod tests {
-    use ci_core::game::GameResult;
-    use ci_png_parser::parse;
+    use std::{fs::File, io::BufReader, path::PathBuf};
+
+    use ci_core::{
+        game::{Game, GameResult},
+        tags::Tags,
+    };
+    use ci_png_parser::{parse, write, GameReader};
     use glob::glob;
 
+    /// The games of every file in the corpus, read one after the other.
+    fn corpus() -> Vec<(PathBuf, Vec<Game>)> {
+        // Integration tests run from the crate directory
+        let files = glob("../../tests/games/*.pgn")
+            .expect("Failed to read glob pattern")
+            .map(|entry| {
+                let path = entry.unwrap();
+                let file = File::open(&path).unwrap();
+                let games = GameReader::new(BufReader::new(file))
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+
+                assert!(!games.is_empty(), "{} holds no games", path.display());
+                (path, games)
+            })
+            .collect::<Vec<_>>();
+
+        assert!(!files.is_empty(), "No games found in tests/games");
+        files
+    }
+
     #[test]
     fn should_parse() {
-        for entry in glob("tests/games/*.pgn").expect("Failed to read glob pattern") {
-            match entry {
-                Ok(path) => {
-                    println!("{:?}", path.display());
-                    let contents = std::fs::read_to_string(path).unwrap();
-                    let game = pgn::parse(&contents);
-
-                    assert!(
-                        game.result != GameResult::Unknown,
-                        "Game has unknown outcome: {:?}",
-                        game
-                    );
+        for (path, games) in corpus() {
+            for game in games {
+                assert!(
+                    game.result != GameResult::Unknown,
+                    "{}: game has unknown outcome: {:?}",
+                    path.display(),
+                    game
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_replay() {
+        for (path, games) in corpus() {
+            for game in games {
+                for position in game.positions() {
+                    if let Err(error) = position {
+                        panic!("{}: {}", path.display(), error);
+                    }
                 }
-                Err(e) => println!("{:?}", e),
+
+                assert!(
+                    game.diagnostics.is_empty(),
+                    "{}: {:?}",
+                    path.display(),
+                    game.diagnostics
+                );
             }
         }
     }
+
+    #[test]
+    fn should_round_trip() {
+        fn sorted(tags: &Tags) -> Vec<(&str, &str)> {
+            let mut tags = tags.iter().collect::<Vec<_>>();
+            tags.sort();
+            tags
+        }
+
+        for (path, games) in corpus() {
+            for game in games {
+                let written = write(&game);
+                let reparsed = parse(&written).expect("Could not parse written game");
+
+                assert_eq!(reparsed.comments, game.comments, "{}", path.display());
+                assert_eq!(reparsed.moves, game.moves, "{}", path.display());
+                assert_eq!(
+                    sorted(&reparsed.tags),
+                    sorted(&game.tags),
+                    "{}",
+                    path.display()
+                );
+                assert_eq!(reparsed.result, game.result, "{}", path.display());
+                assert_eq!(write(&reparsed), written, "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn should_parse_without_moves() {
+        let contents = std::fs::read_to_string("../../tests/fixtures/no_show.pgn").unwrap();
+        let game = parse(&contents).expect("Could not parse game");
+
+        assert!(game.moves.is_empty());
+        assert_eq!(game.result, GameResult::BlackWins);
+    }
 }